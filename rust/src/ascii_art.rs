@@ -1,5 +1,6 @@
 /// ASCII art rendering using Unicode block elements.
 /// Each character maps to a 2-line boxed art representation.
+/// Also holds the box-drawing glyphs used for table borders.
 use std::collections::HashMap;
 use std::sync::OnceLock;
 
@@ -75,3 +76,36 @@ pub fn is_boxed_art_supported(text: &str) -> bool {
     let map = boxed_map();
     text.to_lowercase().chars().all(|c| map.contains_key(&c))
 }
+
+/// Box-drawing glyphs for framing tables: corners, edges and junctions.
+pub struct BoxChars {
+    pub horizontal: char,
+    pub vertical: char,
+    pub top: [char; 3],
+    pub middle: [char; 3],
+    pub bottom: [char; 3],
+}
+
+/// Light single-line box: `┌─┬─┐`.
+pub const BOX_LIGHT: BoxChars = BoxChars {
+    horizontal: '─',
+    vertical: '│',
+    top: ['┌', '┬', '┐'],
+    middle: ['├', '┼', '┤'],
+    bottom: ['└', '┴', '┘'],
+};
+
+/// Draws a horizontal border for columns of the given widths, using `[left, junction, right]` glyphs.
+/// Each column is padded by one space on either side.
+pub fn box_rule(widths: &[usize], glyphs: [char; 3], horizontal: char) -> String {
+    let mut line = String::new();
+    line.push(glyphs[0]);
+    for (i, width) in widths.iter().enumerate() {
+        if i > 0 {
+            line.push(glyphs[1]);
+        }
+        line.extend(std::iter::repeat_n(horizontal, width + 2));
+    }
+    line.push(glyphs[2]);
+    line
+}
@@ -10,6 +10,9 @@ use godot::classes::display_server::VSyncMode;
 use godot::classes::{file_access::ModeFlags, Engine, Expression, FileAccess, ProjectSettings};
 use godot::prelude::*;
 
+use crate::command_history;
+use crate::output_buffer::{LogLevel, OutputFilter};
+use crate::table::Table;
 use crate::tiny_console::TinyConsole;
 use crate::util;
 
@@ -44,23 +47,19 @@ impl BuiltinCommands {
         let mut s = console.bind_mut();
         let mut alias_names: Vec<String> = s.aliases.keys().cloned().collect();
         alias_names.sort();
+        let mut table = Table::new(Vec::new());
+        table.set_column_color(2, s.output_debug_color);
         for alias in alias_names {
             let argv = s.aliases.get(&alias).unwrap().clone();
             let cmd_name = &argv[0];
             let desc = s.command_descriptions.get(cmd_name).cloned().unwrap_or_default();
-            let color = s.output_command_mention_color.to_html();
-            let formatted_alias = format!("[color={}]{}[/color]", color, alias);
-            if desc.is_empty() {
-                s.print_line_internal(&formatted_alias, false);
-            } else {
-                let formatted_cmd = format!("[color={}]{}[/color]", color, cmd_name);
-                let rest = argv[1..].join(" ");
-                let debug_color = s.output_debug_color.to_html();
-                let tip = format!("[i][color={}] // {}[/color][/i]", debug_color, desc);
-                let msg = format!("{} is alias of: {} {} {}", formatted_alias, formatted_cmd, rest, tip);
-                s.print_line_internal(&msg, false);
-            }
+            let formatted_alias = s.format_name(GString::from(alias.as_str()));
+            let formatted_cmd = s.format_name(GString::from(cmd_name.as_str()));
+            let expansion = format!("=> {} {}", formatted_cmd, argv[1..].join(" "));
+            let tip = if desc.is_empty() { String::new() } else { format!("[i]// {}[/i]", desc) };
+            table.add_row(vec![formatted_alias.to_string(), expansion.trim_end().to_string(), tip]);
         }
+        s.print_table_internal(&table, false);
     }
 
     #[func]
@@ -70,16 +69,50 @@ impl BuiltinCommands {
         s.print_line_internal("Available commands:", false);
         let mut names: Vec<String> = s.commands.keys().cloned().collect();
         names.sort();
-        let color = s.output_command_mention_color.to_html();
+        let mut table = Table::new(Vec::new());
         for name in &names {
             let desc = s.command_descriptions.get(name).cloned().unwrap_or_default();
-            let formatted = format!("[color={}]{}[/color]", color, name);
-            if desc.is_empty() {
-                s.print_line_internal(&formatted, false);
-            } else {
-                let msg = format!("{} -- {}", formatted, desc);
-                s.print_line_internal(&msg, false);
+            let formatted = s.format_name(GString::from(name.as_str()));
+            table.add_row(vec![formatted.to_string(), desc]);
+        }
+        s.print_table_internal(&table, false);
+    }
+
+    #[func]
+    fn cmd_copy(&self) {
+        let mut console = TinyConsole::singleton();
+        let mut s = console.bind_mut();
+        match s.copy_command_output(true) {
+            Some(count) => {
+                let msg = format!("Copied {} line{} to the clipboard.", count, if count == 1 { "" } else { "s" });
+                s.info(GString::from(msg.as_str()));
             }
+            None => s.error("Nothing to copy: the previous command printed no output.".into()),
+        }
+    }
+
+    #[func]
+    fn cmd_dump(&self, args: GString) {
+        let mut console = TinyConsole::singleton();
+        let mut s = console.bind_mut();
+
+        let args_str = args.to_string();
+        let (file, bbcode) = util::take_flag(&args_str, "--bbcode");
+        let file = util::unquote(file);
+        if file.is_empty() {
+            s.error("Expected a file name.".into());
+            s.print_line_internal("Usage: dump <file> [lb]--bbcode[rb]", false);
+            return;
+        }
+
+        let path = util::resolve_user_path(file);
+        if s.dump_output(GString::from(path.as_str()), bbcode) {
+            let global_path = ProjectSettings::singleton().globalize_path(path.as_str());
+            let msg = format!("Output saved to {}", util::bbcode_escape(&global_path.to_string()));
+            s.info(GString::from(msg.as_str()));
+        } else {
+            let msg = format!("Can't write file: {}", path);
+            s.error(GString::from(msg.as_str()));
         }
     }
 
@@ -108,8 +141,7 @@ impl BuiltinCommands {
 
         if !exp.has_execute_failed() {
             if !result.is_nil() {
-                let msg = result.to_string();
-                s.print_line_internal(&msg, false);
+                s.print_variant_internal(result, false);
             }
         } else {
             let err_text = exp.get_error_text();
@@ -131,6 +163,44 @@ impl BuiltinCommands {
         TinyConsole::singleton().bind().execute_script(GString::from(file_str.as_str()), true);
     }
 
+    #[func]
+    fn cmd_filter(&self, args: GString) {
+        let mut console = TinyConsole::singleton();
+        let mut s = console.bind_mut();
+
+        let mut filter = OutputFilter::default();
+        let mut words = Vec::new();
+        let args_str = args.to_string();
+        let mut tokens = args_str.split_whitespace();
+        while let Some(token) = tokens.next() {
+            match token {
+                "--level" => match tokens.next().and_then(LogLevel::from_name) {
+                    Some(level) => filter.level = Some(level),
+                    None => {
+                        s.error("Expected a level after --level: debug, info, warning or error.".into());
+                        return;
+                    }
+                },
+                "--since" => match tokens.next().and_then(util::parse_duration) {
+                    Some(secs) => filter.since = Some(command_history::unix_time_now() as f64 - secs as f64),
+                    None => {
+                        s.error("Expected a duration after --since, e.g. 30s, 10m, 2h, 1d.".into());
+                        return;
+                    }
+                },
+                _ => words.push(token),
+            }
+        }
+        if !words.is_empty() {
+            filter.set_pattern(Some(&words.join(" ")));
+        }
+
+        if !filter.is_active() {
+            s.info("Output filter cleared.".into());
+        }
+        s.set_output_filter(filter);
+    }
+
     #[func]
     fn cmd_fps_max(&self, limit: i32) {
         let mut console = TinyConsole::singleton();
@@ -180,7 +250,7 @@ impl BuiltinCommands {
         if command_name.is_empty() {
             let color = s.output_command_mention_color.to_html();
             let debug_color = s.output_debug_color.to_html();
-            let tip1 = format!("[i][color={}]Type [color={}]commands[/color] to list all available commands.[/color][/i]", debug_color, color);
+            let tip1 = format!("[i][color={}]Type {} to list all available commands.[/color][/i]", debug_color, s.format_name("commands".into()));
             s.print_line_internal(&tip1, false);
             let tip2 = format!("[i][color={}]Type [color={}]help command[/color] to get more info about the command.[/color][/i]", debug_color, color);
             s.print_line_internal(&tip2, false);
@@ -189,6 +259,101 @@ impl BuiltinCommands {
         }
     }
 
+    #[func]
+    fn cmd_history(&self, args: GString) {
+        let mut console = TinyConsole::singleton();
+        let mut s = console.bind_mut();
+
+        let mut count: Option<usize> = None;
+        let mut failed_only = false;
+        let mut since: Option<i64> = None;
+        let args_str = args.to_string();
+        let mut tokens = args_str.split_whitespace();
+        while let Some(token) = tokens.next() {
+            match token {
+                "--failed" => failed_only = true,
+                "--since" => match tokens.next().and_then(util::parse_duration) {
+                    Some(secs) => since = Some(secs),
+                    None => {
+                        s.error("Expected a duration after --since, e.g. 30s, 10m, 2h, 1d.".into());
+                        return;
+                    }
+                },
+                _ => match token.parse::<usize>() {
+                    Ok(n) => count = Some(n),
+                    Err(_) => {
+                        let msg = format!("Invalid argument: {}", token);
+                        s.error(GString::from(msg.as_str()));
+                        s.print_line_internal("Usage: history [lb]count[rb] [lb]--failed[rb] [lb]--since duration[rb]", false);
+                        return;
+                    }
+                },
+            }
+        }
+
+        let now = command_history::unix_time_now();
+        let matching: Vec<usize> = s
+            .history
+            .entries()
+            .iter()
+            .enumerate()
+            .filter(|(_, e)| !failed_only || e.exit_code != 0)
+            .filter(|(_, e)| since.is_none_or(|secs| e.timestamp >= now - secs))
+            .map(|(i, _)| i)
+            .collect();
+        let skip = matching.len() - count.unwrap_or(matching.len()).min(matching.len());
+
+        let mut table = Table::new(Vec::new());
+        table.set_column_color(0, s.output_debug_color);
+        table.set_column_color(1, s.output_debug_color);
+        table.set_column_color(3, s.output_error_color);
+        for &i in &matching[skip..] {
+            let entry = &s.history.entries()[i];
+            let time = if entry.timestamp > 0 { util::format_local_datetime(entry.timestamp) } else { "--".to_string() };
+            let exit = if entry.exit_code != 0 { format!("(exit {})", entry.exit_code) } else { String::new() };
+            table.add_row(vec![(i + 1).to_string(), time, util::bbcode_escape(&entry.command), exit]);
+        }
+        s.print_table_internal(&table, false);
+    }
+
+    #[func]
+    fn cmd_console_reload(&self) {
+        let mut console = TinyConsole::singleton();
+        let mut s = console.bind_mut();
+        s.reload_options();
+        s.info("Console settings reloaded.".into());
+    }
+
+    #[func]
+    fn cmd_dock(&self, dock: GString) {
+        let mut console = TinyConsole::singleton();
+        let mut s = console.bind_mut();
+        if dock.is_empty() {
+            let msg = format!("Dock: {}", s.get_dock());
+            s.info(GString::from(msg.as_str()));
+            return;
+        }
+        if !s.set_dock(dock.clone()) {
+            let msg = format!("Unknown dock: {}. Use top, bottom, left, right or window.", dock);
+            s.error(GString::from(msg.as_str()));
+        }
+    }
+
+    #[func]
+    fn cmd_line_prefix(&self, prefix: GString) {
+        let mut console = TinyConsole::singleton();
+        let mut s = console.bind_mut();
+        if prefix.is_empty() {
+            let msg = format!("Line prefix: {}", s.get_line_prefix());
+            s.info(GString::from(msg.as_str()));
+            return;
+        }
+        if !s.set_line_prefix(prefix.clone()) {
+            let msg = format!("Unknown line prefix: {}. Use none, wall_clock, uptime, process_frame or physics_frame.", prefix);
+            s.error(GString::from(msg.as_str()));
+        }
+    }
+
     #[func]
     fn cmd_log(&self, num_lines: i32) {
         let mut console = TinyConsole::singleton();
@@ -213,12 +378,64 @@ impl BuiltinCommands {
         }
     }
 
+    #[func]
+    fn cmd_loglevel(&self, level: GString) {
+        let mut console = TinyConsole::singleton();
+        let mut s = console.bind_mut();
+        if level.is_empty() {
+            // Printed at the minimum level so the reply isn't hidden by it
+            let msg = format!("Minimum log level: {}", s.min_log_level.name());
+            let level = s.min_log_level.max(LogLevel::Info);
+            s.print_line_level(&msg, level, false);
+            return;
+        }
+        if !s.set_min_log_level(level.clone()) {
+            let msg = format!("Unknown log level: {}. Use debug, info, warning or error.", level);
+            s.error(GString::from(msg.as_str()));
+        }
+    }
+
+    #[func]
+    fn cmd_pause_mode(&self, mode: GString) {
+        let mut console = TinyConsole::singleton();
+        let mut s = console.bind_mut();
+        if mode.is_empty() {
+            let msg = format!("Pause mode: {}", s.get_pause_mode());
+            s.info(GString::from(msg.as_str()));
+            s.print_line_internal(
+                "Modes: none, pause, pause_keep_physics_servers (rigid bodies keep moving; _physics_process of paused nodes doesn't run), slow_motion",
+                false,
+            );
+            return;
+        }
+        if !s.set_pause_mode(mode.clone()) {
+            let msg = format!("Unknown pause mode: {}. Use none, pause, pause_keep_physics_servers or slow_motion.", mode);
+            s.error(GString::from(msg.as_str()));
+        }
+    }
+
     #[func]
     fn cmd_quit(&self) {
         let mut tree = TinyConsole::get_scene_tree();
         tree.quit();
     }
 
+    #[func]
+    fn cmd_theme(&self, theme: GString) {
+        let mut console = TinyConsole::singleton();
+        let mut s = console.bind_mut();
+        if theme.is_empty() {
+            let presets: Vec<String> = s.get_theme_presets().as_slice().iter().map(|name| name.to_string()).collect();
+            let msg = format!("Theme: {}. Available: {}, or a path to a Theme resource.", s.get_theme(), presets.join(", "));
+            s.info(GString::from(msg.as_str()));
+            return;
+        }
+        if !s.set_theme(theme.clone()) {
+            let msg = format!("Unknown theme: {}. Use a preset name or a path to a Theme resource.", theme);
+            s.error(GString::from(msg.as_str()));
+        }
+    }
+
     #[func]
     fn cmd_unalias(&self, alias: GString) {
         let mut console = TinyConsole::singleton();
@@ -280,13 +497,23 @@ pub fn register(console: &mut TinyConsole, builtin: &Gd<BuiltinCommands>) {
     register(console, "cmd_alias", "alias", "add command alias");
     register(console, "cmd_aliases", "aliases", "list all aliases");
     register(console, "cmd_commands", "commands", "list all commands");
+    register(console, "cmd_console_reload", "console_reload", "re-read console settings and theme");
+    register(console, "cmd_copy", "copy", "copy the previous command's output to the clipboard");
+    register(console, "cmd_dock", "dock", "move the console to a screen edge or a separate window");
+    register(console, "cmd_dump", "dump", "save console output to a file");
     register(console, "cmd_eval", "eval", "evaluate an expression");
     register(console, "cmd_exec", "exec", "execute commands from file");
+    register(console, "cmd_filter", "filter", "show only matching output lines");
     register(console, "cmd_fps_max", "fps_max", "limit framerate");
     register(console, "cmd_fullscreen", "fullscreen", "toggle fullscreen mode");
     register(console, "cmd_help", "help", "show command info");
+    register(console, "cmd_history", "history", "list command history");
+    register(console, "cmd_line_prefix", "line_prefix", "show time or frame before output lines");
     register(console, "cmd_log", "log", "show recent log entries");
+    register(console, "cmd_loglevel", "loglevel", "set minimum level of shown output");
+    register(console, "cmd_pause_mode", "pause_mode", "set whether the game pauses while the console is open");
     register(console, "cmd_quit", "quit", "exit the application");
+    register(console, "cmd_theme", "theme", "switch the console theme");
     register(console, "cmd_unalias", "unalias", "remove command alias");
     register(console, "cmd_vsync", "vsync", "adjust V-Sync");
 
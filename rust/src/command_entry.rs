@@ -65,6 +65,27 @@ impl CommandEntry {
     }
 }
 
+impl CommandEntry {
+    /// Caches the theme properties used when drawing the autocomplete hint.
+    fn cache_theme(&mut self) {
+        let font = self.base().get_theme_font_ex("font").done();
+        let font_size = self.base().get_theme_font_size_ex("font_size").done();
+        if let Some(f) = font {
+            self.font = Some(f);
+        }
+        self.font_size = font_size;
+
+        if self.base().has_theme_color_ex("hint_color").done() {
+            self.hint_color = self.base().get_theme_color_ex("hint_color").done();
+        }
+
+        let sb = self.base().get_theme_stylebox_ex("normal").done();
+        if let Some(s) = sb {
+            self.sb_normal = Some(s);
+        }
+    }
+}
+
 #[godot_api]
 impl ITextEdit for CommandEntry {
     fn init(base: Base<TextEdit>) -> Self {
@@ -95,22 +116,7 @@ impl ITextEdit for CommandEntry {
             hscroll.set_visible(false);
         }
 
-        // Cache theme properties
-        let font = self.base().get_theme_font_ex("font").done();
-        let font_size = self.base().get_theme_font_size_ex("font_size").done();
-        if let Some(f) = font {
-            self.font = Some(f);
-        }
-        self.font_size = font_size;
-
-        if self.base().has_theme_color_ex("hint_color").done() {
-            self.hint_color = self.base().get_theme_color_ex("hint_color").done();
-        }
-
-        let sb = self.base().get_theme_stylebox_ex("normal").done();
-        if let Some(s) = sb {
-            self.sb_normal = Some(s);
-        }
+        self.cache_theme();
 
         // Set syntax highlighter
         let highlighter = CommandEntryHighlighter::new_gd();
@@ -237,7 +243,7 @@ impl ITextEdit for CommandEntry {
 
         let offset_x = (sb_offset.x * 0.5) + self.base().get_line_width(0) as f32;
         let offset_y = (sb_offset.y * 0.5) + self.base().get_line_height() as f32 + 0.5
-            - font.get_descent() as f32;
+            - font.get_descent();
 
         // Copy values to avoid borrow conflict with base_mut()
         let hint = self.autocomplete_hint.clone();
@@ -255,6 +261,11 @@ impl ITextEdit for CommandEntry {
         match what {
             ControlNotification::FOCUS_ENTER => self.base_mut().set_process_input(true),
             ControlNotification::FOCUS_EXIT => self.base_mut().set_process_input(false),
+            // The hint is drawn with the cached font and color
+            ControlNotification::THEME_CHANGED if self.base().is_node_ready() => {
+                self.cache_theme();
+                self.base_mut().queue_redraw();
+            }
             _ => {}
         }
     }
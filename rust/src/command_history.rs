@@ -1,35 +1,272 @@
-/// Command history: storage, load/save, fuzzy match, wrapping iterator.
+/// Command history: storage, load/save, fuzzy match, navigation cursor.
 use godot::classes::file_access::ModeFlags;
-use godot::classes::FileAccess;
+use godot::classes::{FileAccess, Os, RegEx};
 use godot::prelude::*;
+use std::time::{SystemTime, UNIX_EPOCH};
 
-pub const HISTORY_FILE: &str = "user://tiny_console_history.log";
+use crate::util;
+
+pub const DEFAULT_HISTORY_FILE: &str = "user://tiny_console_history.log";
+
+/// Command-line argument selecting a history profile: `--tiny-console-history-profile=<name>`.
+pub const PROFILE_CMDLINE_ARG: &str = "--tiny-console-history-profile";
+
+/// First line of a structured history file. Files without it are read as plain text (one command per line).
+const FORMAT_HEADER: &str = "#tiny_console_history 2";
+/// Start of the header line of any version of the structured format.
+const FORMAT_HEADER_PREFIX: &str = "#tiny_console_history ";
+
+/// A single history record. Duplicated commands share one record.
+#[derive(Clone)]
+pub struct HistoryEntry {
+    pub command: String,
+    /// Unix time of the last run, or 0 if unknown (migrated from a plain-text file).
+    pub timestamp: i64,
+    /// Exit status of the last run: the command's return code, 127 if unknown, 2 on bad arguments.
+    pub exit_code: i32,
+    pub run_count: u32,
+    /// Scene the command last ran in (`scene_file_path` of the current scene).
+    pub scene: String,
+    /// Lowercase copy of `command`, cached for fuzzy search.
+    lowercase: String,
+}
+
+impl HistoryEntry {
+    fn from_command(command: String) -> Self {
+        Self {
+            lowercase: lowercase(&command),
+            command,
+            timestamp: 0,
+            exit_code: 0,
+            run_count: 1,
+            scene: String::new(),
+        }
+    }
+
+    fn parse(line: &str) -> Option<Self> {
+        let mut fields = line.splitn(5, '\t');
+        let timestamp = fields.next()?.parse().ok()?;
+        let exit_code = fields.next()?.parse().ok()?;
+        let run_count = fields.next()?.parse().ok()?;
+        let scene = unescape_field(fields.next()?);
+        let command = unescape_field(fields.next()?.trim());
+        if command.is_empty() {
+            return None;
+        }
+        Some(Self {
+            lowercase: lowercase(&command),
+            command,
+            timestamp,
+            exit_code,
+            run_count,
+            scene,
+        })
+    }
+
+    fn serialize(&self) -> String {
+        format!(
+            "{}\t{}\t{}\t{}\t{}",
+            self.timestamp,
+            self.exit_code,
+            self.run_count,
+            escape_field(&self.scene),
+            escape_field(&self.command)
+        )
+    }
+}
+
+/// Escapes backslashes, tabs and line breaks, so a field never spans columns or lines.
+fn escape_field(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '\t' => escaped.push_str("\\t"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Reverses `escape_field`. Unknown escapes are kept as they are.
+fn unescape_field(text: &str) -> String {
+    let mut unescaped = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('\\') => unescaped.push('\\'),
+            Some('t') => unescaped.push('\t'),
+            Some('n') => unescaped.push('\n'),
+            Some('r') => unescaped.push('\r'),
+            Some(other) => {
+                unescaped.push('\\');
+                unescaped.push(other);
+            }
+            None => unescaped.push('\\'),
+        }
+    }
+    unescaped
+}
+
+/// How repeated commands are recorded (like bash's `ignoredups` / `erasedups`).
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum DuplicateMode {
+    /// Every run gets its own entry.
+    KeepAll,
+    /// A command identical to the previous entry only updates that entry.
+    IgnoreConsecutive,
+    /// Older copies of the command are removed and a single entry is moved to the end.
+    ErasePrevious,
+}
+
+impl DuplicateMode {
+    /// Setting values, in the order shown in Project Settings.
+    pub const SETTING_VALUES: &'static str = "erase_previous,ignore_consecutive,keep_all";
+
+    pub fn from_setting(value: &str) -> Self {
+        match value {
+            "keep_all" => Self::KeepAll,
+            "ignore_consecutive" => Self::IgnoreConsecutive,
+            "erase_previous" => Self::ErasePrevious,
+            _ => {
+                godot_error!("TinyConsole: Unknown history duplicates mode: {}", value);
+                Self::ErasePrevious
+            }
+        }
+    }
+}
+
+/// Rules deciding which commands are recorded in history (like bash's `HISTCONTROL` and `HISTIGNORE`).
+pub struct HistoryFilter {
+    /// Don't record commands typed with a leading space.
+    pub ignore_space: bool,
+    pub duplicates: DuplicateMode,
+    /// Don't record commands that failed (unknown command, bad arguments, or non-zero return code).
+    pub ignore_failed: bool,
+    ignore_patterns: Vec<Gd<RegEx>>,
+}
+
+impl HistoryFilter {
+    pub fn new(ignore_space: bool, duplicates: DuplicateMode, ignore_failed: bool, ignore_patterns: &[String]) -> Self {
+        let ignore_patterns = ignore_patterns
+            .iter()
+            .filter_map(|pattern| {
+                let regex = RegEx::create_from_string(pattern);
+                if regex.is_none() {
+                    godot_error!("TinyConsole: Invalid history ignore pattern: {}", pattern);
+                }
+                regex
+            })
+            .collect();
+        Self {
+            ignore_space,
+            duplicates,
+            ignore_failed,
+            ignore_patterns,
+        }
+    }
+
+    fn is_ignored(&self, command: &str, exit_code: i32) -> bool {
+        (self.ignore_failed && exit_code != 0) || self.matches_ignore_pattern(command)
+    }
+
+    fn matches_ignore_pattern(&self, command: &str) -> bool {
+        self.ignore_patterns.iter().any(|regex| regex.search(command).is_some())
+    }
+}
+
+impl Default for HistoryFilter {
+    fn default() -> Self {
+        Self::new(false, DuplicateMode::ErasePrevious, false, &[])
+    }
+}
+
+/// Fuzzy search results for one query.
+struct SearchStep {
+    query: String,
+    /// `(entry index, score)` pairs, newest entry first.
+    matches: Vec<(usize, i32)>,
+    /// Entry indices of `matches`, best match first. Ties keep recency order (sort is stable).
+    ranked: Vec<usize>,
+}
+
+impl SearchStep {
+    fn new(query: String, matches: Vec<(usize, i32)>) -> Self {
+        let mut sorted = matches.clone();
+        sorted.sort_by_key(|m| std::cmp::Reverse(m.1));
+        let ranked = sorted.into_iter().map(|(idx, _)| idx).collect();
+        Self { query, matches, ranked }
+    }
+}
 
 pub struct CommandHistory {
-    entries: Vec<String>,
+    entries: Vec<HistoryEntry>,
+    filter: HistoryFilter,
     is_dirty: bool,
+    /// Results of previous queries, each one extending the query before it, starting with the empty
+    /// query (all entries). Typing narrows the last step's matches instead of rescanning all entries;
+    /// deleting characters pops back to a cached step.
+    /// Cleared whenever entries change, since it stores indices.
+    search_steps: Vec<SearchStep>,
+    /// Incremented whenever entries change, so a `HistoryCursor` knows its position is stale.
+    revision: u64,
 }
 
 impl CommandHistory {
     pub fn new() -> Self {
         Self {
             entries: Vec::new(),
+            filter: HistoryFilter::default(),
             is_dirty: false,
+            search_steps: Vec::new(),
+            revision: 0,
         }
     }
 
-    /// Adds a command to history. Duplicates are moved to the end.
-    pub fn push_entry(&mut self, entry: String) {
-        if let Some(idx) = self.entries.iter().position(|e| e == &entry) {
-            self.entries.remove(idx);
+    pub fn filter(&self) -> &HistoryFilter {
+        &self.filter
+    }
+
+    pub fn set_filter(&mut self, filter: HistoryFilter) {
+        self.filter = filter;
+    }
+
+    /// Adds a command to history, unless the filter ignores it.
+    /// Duplicates are handled according to the filter's `DuplicateMode`; a reused entry has its run count incremented.
+    pub fn push_entry(&mut self, command: String, scene: String, exit_code: i32) {
+        if self.filter.is_ignored(&command, exit_code) {
+            return;
         }
+        let mut entry = match self.find_duplicate(&command) {
+            Some(idx) => {
+                let mut existing = self.entries.remove(idx);
+                existing.run_count += 1;
+                existing
+            }
+            None => HistoryEntry::from_command(command),
+        };
+        entry.timestamp = unix_time_now();
+        entry.exit_code = exit_code;
+        entry.scene = scene;
         self.entries.push(entry);
         self.is_dirty = true;
+        self.entries_changed();
+    }
+
+    fn entries_changed(&mut self) {
+        self.search_steps.clear();
+        self.revision += 1;
     }
 
     pub fn get_entry(&self, index: usize) -> &str {
         let idx = index.min(self.entries.len().saturating_sub(1));
-        &self.entries[idx]
+        &self.entries[idx].command
     }
 
     pub fn size(&self) -> usize {
@@ -40,30 +277,110 @@ impl CommandHistory {
         if self.entries.len() > max_size {
             let drain_count = self.entries.len() - max_size;
             self.entries.drain(..drain_count);
+            self.entries_changed();
         }
     }
 
     pub fn clear(&mut self) {
         self.entries.clear();
         self.is_dirty = true;
+        self.entries_changed();
     }
 
+    /// Removes every entry for the command. Returns `false` if it wasn't in history.
+    pub fn remove_command(&mut self, command: &str) -> bool {
+        let len = self.entries.len();
+        self.entries.retain(|e| e.command != command);
+        if self.entries.len() == len {
+            return false;
+        }
+        self.is_dirty = true;
+        self.entries_changed();
+        true
+    }
+
+    /// Replaces in-memory history with the contents of another file.
+    pub fn reload(&mut self, path: &str) {
+        self.entries.clear();
+        self.entries_changed();
+        self.is_dirty = false;
+        self.load(path);
+    }
+
+    /// Loads history from a file. Structured files start with `FORMAT_HEADER`;
+    /// anything else is migrated from the old plain-text format (one command per line).
     pub fn load(&mut self, path: &str) {
         let path_gstr: GString = path.into();
         if let Some(file) = FileAccess::open(&path_gstr, ModeFlags::READ) {
+            let mut lines = Vec::new();
             while !file.eof_reached() {
-                let line = file.get_line().to_string();
-                let line = line.trim().to_string();
-                if !line.is_empty() {
-                    // Push without dedup reset (internal push)
-                    if let Some(idx) = self.entries.iter().position(|e| e == &line) {
-                        self.entries.remove(idx);
-                    }
-                    self.entries.push(line);
+                lines.push(file.get_line().to_string());
+            }
+            if !self.read_lines(lines) {
+                godot_warn!("TinyConsole: History file {} is from a newer version; reading what it can.", path);
+            }
+        }
+    }
+
+    /// Adds the entries of a history file's lines. Lines that can't be parsed are skipped.
+    /// Returns `false` if the file has a version header this build doesn't know; its lines are
+    /// then read as the current version, and the file isn't rewritten until history changes.
+    fn read_lines(&mut self, lines: impl IntoIterator<Item = String>) -> bool {
+        self.entries_changed();
+        let mut structured = false;
+        let mut known_format = true;
+        let mut first_line = true;
+        let mut dropped_ignored = false;
+        for line in lines {
+            let line = line.trim();
+            if first_line {
+                first_line = false;
+                if line.starts_with(FORMAT_HEADER_PREFIX) {
+                    structured = true;
+                    known_format = line == FORMAT_HEADER;
+                    continue;
                 }
             }
-            self.is_dirty = false;
+            if line.is_empty() {
+                continue;
+            }
+            let entry = if structured {
+                match HistoryEntry::parse(line) {
+                    Some(entry) => entry,
+                    None => continue,
+                }
+            } else {
+                HistoryEntry::from_command(line.to_string())
+            };
+            // Drop entries persisted before a matching ignore pattern was added.
+            if self.filter.matches_ignore_pattern(&entry.command) {
+                dropped_ignored = true;
+                continue;
+            }
+            self.insert_loaded(entry);
         }
+        // A migrated or filtered file needs to be rewritten.
+        self.is_dirty = (!structured && !self.entries.is_empty()) || dropped_ignored;
+        known_format
+    }
+
+    /// Returns the index of the entry a new run of `command` should be merged into, per the `DuplicateMode`.
+    fn find_duplicate(&self, command: &str) -> Option<usize> {
+        match self.filter.duplicates {
+            DuplicateMode::KeepAll => None,
+            DuplicateMode::IgnoreConsecutive => self.entries.len().checked_sub(1).filter(|&i| self.entries[i].command == command),
+            DuplicateMode::ErasePrevious => self.entries.iter().position(|e| e.command == command),
+        }
+    }
+
+    /// Inserts a loaded entry, merging it with a duplicate per the `DuplicateMode`.
+    fn insert_loaded(&mut self, mut entry: HistoryEntry) {
+        if let Some(idx) = self.find_duplicate(&entry.command) {
+            let existing = self.entries.remove(idx);
+            entry.run_count += existing.run_count;
+            entry.timestamp = entry.timestamp.max(existing.timestamp);
+        }
+        self.entries.push(entry);
     }
 
     pub fn save(&mut self, path: &str) {
@@ -72,8 +389,9 @@ impl CommandHistory {
         }
         let path_gstr: GString = path.into();
         if let Some(mut file) = FileAccess::open(&path_gstr, ModeFlags::WRITE) {
-            for line in &self.entries {
-                file.store_line(&GString::from(line.as_str()));
+            file.store_line(FORMAT_HEADER);
+            for entry in &self.entries {
+                file.store_line(&GString::from(entry.serialize().as_str()));
             }
             self.is_dirty = false;
         } else {
@@ -84,126 +402,547 @@ impl CommandHistory {
         }
     }
 
-    /// Returns entries matching the query, sorted by relevance (best first).
-    pub fn fuzzy_match(&self, query: &str) -> Vec<String> {
-        if query.is_empty() {
-            let mut copy = self.entries.clone();
-            copy.reverse();
-            return copy;
+    /// Returns the indices of entries matching the query, sorted by relevance (best first).
+    /// An empty query matches all entries, newest first.
+    /// Match score is combined with a frecency bonus so recently and frequently used commands rank higher.
+    pub fn fuzzy_match(&mut self, query: &str) -> &[usize] {
+        let query_lower = lowercase(query);
+        while self.search_steps.last().is_some_and(|step| !query_lower.starts_with(&step.query)) {
+            self.search_steps.pop();
+        }
+        if self.search_steps.is_empty() {
+            let all = (0..self.entries.len()).rev().map(|idx| (idx, 0)).collect();
+            self.search_steps.push(SearchStep::new(String::new(), all));
         }
+        if self.search_steps.last().is_some_and(|step| step.query != query_lower) {
+            let matches = self.score_candidates(&query_lower);
+            self.search_steps.push(SearchStep::new(query_lower, matches));
+        }
+        self.search_steps.last().map_or(&[], |step| step.ranked.as_slice())
+    }
+
+    /// Scores the previous step's matches for a query that extends it
+    /// (a subsequence match of the longer query implies a match of the shorter one).
+    fn score_candidates(&self, query_lower: &str) -> Vec<(usize, i32)> {
+        let now = unix_time_now();
+        let score = |idx: usize| {
+            let entry = &self.entries[idx];
+            let score = compute_match_score(query_lower, &entry.lowercase);
+            (score > 0).then(|| (idx, score + frecency_bonus(entry, now)))
+        };
+        match self.search_steps.last() {
+            Some(step) => step.matches.iter().filter_map(|&(idx, _)| score(idx)).collect(),
+            None => Vec::new(),
+        }
+    }
+
+    /// Performs bash-style history expansion on a command line.
+    /// Supports `!!` (last command), `!n` (entry number n), `!-n` (n-th previous),
+    /// `!prefix` (most recent command starting with prefix) and `^old^new` (quick substitution
+    /// on the last command). Designators are only recognized at the start of a word and outside quotes.
+    /// Returns an error message if a designator can't be resolved.
+    pub fn expand(&self, line: &str) -> Result<String, String> {
+        if let Some(rest) = line.strip_prefix('^') {
+            return self.quick_substitute(rest);
+        }
+
+        let chars: Vec<char> = line.chars().collect();
+        let mut result = String::with_capacity(line.len());
+        let mut in_quotes = false;
+        let mut i = 0usize;
+
+        while i < chars.len() {
+            let c = chars[i];
+            if c == '"' {
+                in_quotes = !in_quotes;
+            }
+            let at_word_start = i == 0 || chars[i - 1] == ' ';
+            if c == '!' && !in_quotes && at_word_start && i + 1 < chars.len() {
+                let designator: String = chars[i + 1..].iter().take_while(|&&ch| ch != ' ').collect();
+                if let Some(resolved) = self.resolve_designator(&designator)? {
+                    result.push_str(resolved);
+                    i += 1 + designator.chars().count();
+                    continue;
+                }
+            }
+            result.push(c);
+            i += 1;
+        }
+        Ok(result)
+    }
+
+    /// Resolves the text following `!`. Returns `Ok(None)` if it is not a history designator
+    /// and should be kept literally (e.g. `!=` or `!(`).
+    fn resolve_designator(&self, designator: &str) -> Result<Option<&str>, String> {
+        let not_found = || format!("Event not found: !{}", designator);
 
-        let query_lower = query.to_lowercase();
-        let mut results: Vec<(String, i32)> = Vec::new();
+        if designator == "!" {
+            return self.entries.last().map(|e| Some(e.command.as_str())).ok_or_else(not_found);
+        }
 
-        for entry in &self.entries {
-            let score = compute_match_score(&query_lower, &entry.to_lowercase());
-            if score > 0 {
-                results.push((entry.clone(), score));
+        if let Ok(n) = designator.parse::<i64>() {
+            let len = self.size() as i64;
+            let idx = if n > 0 { n - 1 } else { len + n };
+            if n == 0 || idx < 0 || idx >= len {
+                return Err(not_found());
             }
+            return Ok(Some(self.get_entry(idx as usize)));
+        }
+
+        if designator.starts_with(|c: char| c.is_alphabetic() || c == '_') {
+            return self
+                .entries
+                .iter()
+                .rev()
+                .find(|e| e.command.starts_with(designator))
+                .map(|e| Some(e.command.as_str()))
+                .ok_or_else(not_found);
         }
 
-        results.sort_by(|a, b| b.1.cmp(&a.1));
-        results.into_iter().map(|(entry, _)| entry).collect()
+        Ok(None)
     }
 
-    pub fn entries(&self) -> &[String] {
+    /// Handles `^old^new[^]`: repeats the last command, replacing the first occurrence of `old` with `new`.
+    fn quick_substitute(&self, spec: &str) -> Result<String, String> {
+        let mut parts = spec.splitn(3, '^');
+        let old = parts.next().unwrap_or("");
+        let new = parts.next().unwrap_or("");
+        let last = &self.entries.last().ok_or_else(|| "Event not found: no previous command".to_string())?.command;
+        if old.is_empty() || !last.contains(old) {
+            return Err(format!("Substitution failed: {}", old));
+        }
+        Ok(last.replacen(old, new, 1))
+    }
+
+    pub fn entries(&self) -> &[HistoryEntry] {
         &self.entries
     }
+}
 
-    pub fn create_iterator(&self) -> WrappingIterator {
-        WrappingIterator {
-            idx: -1,
-            entries: self.entries.clone(),
-        }
+/// Returns the char indices in `entry.command` matched by `query`. Empty if the query doesn't match.
+/// Runs the same greedy match as the search score over the same lowercase copy, then maps the
+/// matched chars back to the command chars they were lowercased from.
+pub fn match_positions(query: &str, entry: &HistoryEntry) -> Vec<usize> {
+    let mut lower_positions = Vec::new();
+    if greedy_match(&lowercase(query), &entry.lowercase, |i| lower_positions.push(i)) == 0 {
+        return Vec::new();
     }
+    // One command char can lowercase to several chars (`İ` becomes `i̇`).
+    let origins: Vec<usize> = entry
+        .command
+        .chars()
+        .enumerate()
+        .flat_map(|(i, c)| std::iter::repeat_n(i, c.to_lowercase().count()))
+        .collect();
+    let mut positions: Vec<usize> = lower_positions.into_iter().map(|i| origins[i]).collect();
+    positions.dedup();
+    positions
+}
 
-    /// Reassigns iterator entries to match the current history.
-    pub fn reassign_iterator(&self, iter: &mut WrappingIterator) {
-        iter.idx = -1;
-        iter.entries = self.entries.clone();
+/// Returns the history file for a profile by inserting the profile name before the extension:
+/// `user://history.log` with profile `server` becomes `user://history.server.log`.
+/// An empty profile returns the base path unchanged.
+pub fn profile_path(base: &str, profile: &str) -> String {
+    if profile.is_empty() {
+        return base.to_string();
     }
+    util::path_with_suffix(base, profile)
+}
+
+/// Profile names may only contain ASCII letters, digits, `_` and `-`, so they are safe in file names.
+pub fn is_valid_profile_name(name: &str) -> bool {
+    name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+}
+
+/// Reads the history profile from command-line arguments, accepting both
+/// `--tiny-console-history-profile=<name>` and `--tiny-console-history-profile <name>`.
+/// User arguments (after `--`) are checked first.
+pub fn profile_from_cmdline() -> Option<String> {
+    let mut os = Os::singleton();
+    let mut args: Vec<String> = os.get_cmdline_user_args().as_slice().iter().map(|a| a.to_string()).collect();
+    args.extend(os.get_cmdline_args().as_slice().iter().map(|a| a.to_string()));
+
+    let prefix = format!("{}=", PROFILE_CMDLINE_ARG);
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        if let Some(value) = arg.strip_prefix(&prefix) {
+            return Some(value.to_string());
+        }
+        if arg == PROFILE_CMDLINE_ARG {
+            return iter.next().cloned();
+        }
+    }
+    None
+}
+
+/// Current Unix time in whole seconds.
+pub fn unix_time_now() -> i64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs() as i64)
+}
+
+/// Ranking bonus for commands that were used recently or often.
+/// Recency decays over days; frequency grows logarithmically with the run count.
+fn frecency_bonus(entry: &HistoryEntry, now: i64) -> i32 {
+    let recency = if entry.timestamp > 0 {
+        let age_days = (now - entry.timestamp).max(0) as f64 / 86400.0;
+        20.0 / (1.0 + age_days)
+    } else {
+        0.0
+    };
+    let frequency = 8.0 * (entry.run_count as f64).ln();
+    (recency + frequency) as i32
+}
+
+/// Lowercases `text` one char at a time, so every char of the result comes from one char of `text`
+/// (`str::to_lowercase` treats a word-final `Σ` differently).
+fn lowercase(text: &str) -> String {
+    text.chars().flat_map(char::to_lowercase).collect()
 }
 
 /// Scoring function for fuzzy matching.
 fn compute_match_score(query: &str, target: &str) -> i32 {
-    if query == target {
-        return 99999;
-    }
+    greedy_match(query, target, |_| {})
+}
 
-    let query_chars: Vec<char> = query.chars().collect();
-    let target_chars: Vec<char> = target.chars().collect();
+/// Greedy subsequence match of `query` in `target`, both already lowercase.
+/// Calls `on_match` with the char index in `target` of each matched char and returns the score,
+/// or 0 if `target` doesn't contain the whole query.
+fn greedy_match(query: &str, target: &str, mut on_match: impl FnMut(usize)) -> i32 {
+    let mut query_chars = query.chars().peekable();
     let mut score = 0i32;
-    let mut query_index = 0usize;
+    let mut prev_char: Option<char> = None;
 
-    for (i, &tc) in target_chars.iter().enumerate() {
-        if query_index < query_chars.len() && tc == query_chars[query_index] {
-            score += 10;
-            if i == 0 || target_chars[i - 1] == ' ' {
-                score += 5; // Bonus for word start
-            }
-            query_index += 1;
-            if query_index == query_chars.len() {
-                break;
+    for (i, tc) in target.chars().enumerate() {
+        match query_chars.peek() {
+            Some(&qc) if qc == tc => {
+                score += 10;
+                if prev_char.is_none_or(|c| c == ' ') {
+                    score += 5; // Bonus for word start
+                }
+                on_match(i);
+                query_chars.next();
             }
+            Some(_) => {}
+            None => break,
         }
+        prev_char = Some(tc);
     }
 
-    if query_index == query_chars.len() {
+    if query == target {
+        99999
+    } else if query_chars.peek().is_none() {
         score
     } else {
         0
     }
 }
 
-/// Circular iterator for navigating history entries.
-pub struct WrappingIterator {
-    idx: i32,
-    entries: Vec<String>,
+/// Circular cursor for navigating history entries with Up/Down.
+/// Only stores a position, so moving it never copies the history. It starts over from the empty
+/// line whenever history changes.
+pub struct HistoryCursor {
+    // None: past the newest entry (empty input line).
+    idx: Option<usize>,
+    // History revision `idx` points into.
+    revision: u64,
 }
 
-impl WrappingIterator {
-    pub fn prev(&mut self) -> String {
-        if self.entries.is_empty() {
+impl HistoryCursor {
+    pub fn new() -> Self {
+        Self { idx: None, revision: 0 }
+    }
+
+    pub fn prev(&mut self, history: &CommandHistory) -> String {
+        self.sync(history);
+        let len = history.size();
+        if len == 0 {
             return String::new();
         }
-        self.idx -= 1;
-        let len = self.entries.len() as i32;
-        // Wrap: valid range is -1..len-1
-        if self.idx < -1 {
-            self.idx = len - 1;
+        // Wrap: from the empty line to the newest entry, from the oldest entry back to the empty line.
+        self.idx = match self.idx {
+            None => Some(len - 1),
+            Some(0) => None,
+            Some(i) => Some(i.min(len) - 1),
+        };
+        self.current(history)
+    }
+
+    pub fn next(&mut self, history: &CommandHistory) -> String {
+        self.sync(history);
+        let len = history.size();
+        if len == 0 {
+            return String::new();
         }
-        if self.idx == -1 {
-            String::new()
-        } else {
-            self.entries[self.idx as usize].clone()
+        self.idx = match self.idx {
+            None => Some(0),
+            Some(i) if i + 1 >= len => None,
+            Some(i) => Some(i + 1),
+        };
+        self.current(history)
+    }
+
+    /// Resets the position if history changed since it was taken.
+    fn sync(&mut self, history: &CommandHistory) {
+        if self.revision != history.revision {
+            self.revision = history.revision;
+            self.idx = None;
         }
     }
 
-    pub fn next(&mut self) -> String {
-        if self.entries.is_empty() {
-            return String::new();
+    fn current(&self, history: &CommandHistory) -> String {
+        self.idx.map(|i| history.get_entry(i).to_string()).unwrap_or_default()
+    }
+
+    pub fn reset(&mut self) {
+        self.idx = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(command: &str) -> HistoryEntry {
+        HistoryEntry {
+            timestamp: 1_700_000_000,
+            exit_code: 2,
+            run_count: 3,
+            scene: "res://main.tscn".to_string(),
+            ..HistoryEntry::from_command(command.to_string())
         }
-        self.idx += 1;
-        let len = self.entries.len() as i32;
-        if self.idx >= len {
-            self.idx = -1;
+    }
+
+    fn read(lines: &[&str]) -> (CommandHistory, bool) {
+        let mut history = CommandHistory::new();
+        let known = history.read_lines(lines.iter().map(|l| l.to_string()));
+        (history, known)
+    }
+
+    fn commands(history: &CommandHistory) -> Vec<&str> {
+        history.entries().iter().map(|e| e.command.as_str()).collect()
+    }
+
+    fn history_of(commands: &[&str]) -> CommandHistory {
+        let mut history = CommandHistory::new();
+        for command in commands {
+            history.push_entry(command.to_string(), String::new(), 0);
         }
-        if self.idx == -1 {
-            String::new()
-        } else {
-            self.entries[self.idx as usize].clone()
+        history
+    }
+
+    #[test]
+    fn expand_last_command() {
+        let history = history_of(&["help", "echo hi"]);
+        assert_eq!(history.expand("!!").unwrap(), "echo hi");
+        assert_eq!(history.expand("help !!").unwrap(), "help echo hi");
+    }
+
+    #[test]
+    fn expand_entry_numbers() {
+        let history = history_of(&["help", "echo hi", "clear"]);
+        assert_eq!(history.expand("!1").unwrap(), "help");
+        assert_eq!(history.expand("!3").unwrap(), "clear");
+        assert_eq!(history.expand("!-1").unwrap(), "clear");
+        assert_eq!(history.expand("!-3").unwrap(), "help");
+    }
+
+    #[test]
+    fn expand_prefix() {
+        let history = history_of(&["echo one", "help", "echo two"]);
+        assert_eq!(history.expand("!ec").unwrap(), "echo two");
+        assert_eq!(history.expand("!he").unwrap(), "help");
+    }
+
+    #[test]
+    fn expand_quick_substitution() {
+        let history = history_of(&["echo one one"]);
+        assert_eq!(history.expand("^one^two").unwrap(), "echo two one");
+        assert_eq!(history.expand("^one^two^").unwrap(), "echo two one");
+        assert_eq!(history.expand("^one^").unwrap(), "echo  one");
+        assert!(history.expand("^three^two").is_err());
+        assert!(history_of(&[]).expand("^a^b").is_err());
+    }
+
+    #[test]
+    fn expand_event_not_found() {
+        let history = history_of(&["help"]);
+        assert_eq!(history.expand("!echo").unwrap_err(), "Event not found: !echo");
+        assert!(history.expand("!0").is_err());
+        assert!(history.expand("!2").is_err());
+        assert!(history.expand("!-2").is_err());
+        assert!(history_of(&[]).expand("!!").is_err());
+    }
+
+    #[test]
+    fn expand_keeps_other_exclamation_marks() {
+        let history = history_of(&["help"]);
+        assert_eq!(history.expand("echo a!b").unwrap(), "echo a!b");
+        assert_eq!(history.expand("echo hi!").unwrap(), "echo hi!");
+        assert_eq!(history.expand("echo !").unwrap(), "echo !");
+        assert_eq!(history.expand("eval 1 != 2").unwrap(), "eval 1 != 2");
+        assert_eq!(history.expand("echo \"!!\"").unwrap(), "echo \"!!\"");
+    }
+
+    #[test]
+    fn entry_round_trip() {
+        let original = entry("echo hello world");
+        let parsed = HistoryEntry::parse(&original.serialize()).unwrap();
+        assert_eq!(parsed.command, "echo hello world");
+        assert_eq!(parsed.timestamp, 1_700_000_000);
+        assert_eq!(parsed.exit_code, 2);
+        assert_eq!(parsed.run_count, 3);
+        assert_eq!(parsed.scene, "res://main.tscn");
+        assert_eq!(parsed.lowercase, "echo hello world");
+    }
+
+    #[test]
+    fn entry_with_tabs_newlines_and_backslashes() {
+        let original = entry("echo a\tb\nc\\n d\r");
+        let line = original.serialize();
+        assert!(!line.contains('\n') && !line.contains('\r'));
+        assert_eq!(line.matches('\t').count(), 4);
+        assert_eq!(HistoryEntry::parse(&line).unwrap().command, original.command);
+    }
+
+    #[test]
+    fn garbled_lines_are_skipped() {
+        assert!(HistoryEntry::parse("1700000000\t0\t1").is_none());
+        assert!(HistoryEntry::parse("when\t0\t1\t\techo").is_none());
+        assert!(HistoryEntry::parse("1700000000\t0\t1\t\t   ").is_none());
+
+        let (history, known) = read(&[FORMAT_HEADER, "1700000000\t0\t1\t\techo a", "garbage", "17000", "1700000001\t0\t1\t\techo b"]);
+        assert!(known);
+        assert_eq!(commands(&history), ["echo a", "echo b"]);
+        assert!(!history.is_dirty);
+    }
+
+    #[test]
+    fn plain_text_file_is_migrated() {
+        let (history, known) = read(&["help", "", "echo hi", "help"]);
+        assert!(known);
+        assert_eq!(commands(&history), ["echo hi", "help"]);
+        assert_eq!(history.entries()[1].run_count, 2);
+        assert_eq!(history.entries()[1].timestamp, 0);
+        assert!(history.is_dirty, "a migrated file is rewritten in the structured format");
+    }
+
+    #[test]
+    fn structured_file_is_not_rewritten() {
+        let lines = [FORMAT_HEADER.to_string(), entry("help").serialize()];
+        let mut history = CommandHistory::new();
+        assert!(history.read_lines(lines));
+        assert_eq!(commands(&history), ["help"]);
+        assert!(!history.is_dirty);
+    }
+
+    #[test]
+    fn unknown_version_header() {
+        let (history, known) = read(&["#tiny_console_history 9", "1700000000\t0\t1\t\techo a", "new\tfields"]);
+        assert!(!known);
+        assert_eq!(commands(&history), ["echo a"], "the header is never read as a command");
+        assert!(!history.is_dirty);
+    }
+
+    /// History with fixed timestamps, so ranking doesn't depend on the current time.
+    fn search_history() -> CommandHistory {
+        let commands = [
+            "echo hello", "help echo", "Clear", "eval 1 + 2", "exec autoexec.lcs", "echo HELLO world",
+            "history --failed", "help", "fps_max 60", "fullscreen", "echo e", "alias ee echo",
+        ];
+        let lines = std::iter::once(FORMAT_HEADER.to_string())
+            .chain(commands.iter().enumerate().map(|(i, c)| format!("0\t0\t{}\t\t{}", i % 4 + 1, c)));
+        let mut history = CommandHistory::new();
+        history.read_lines(lines);
+        history
+    }
+
+    fn full_search(history: &CommandHistory, query: &str) -> Vec<usize> {
+        let mut fresh = CommandHistory::new();
+        fresh.entries = history.entries.clone();
+        fresh.fuzzy_match(query).to_vec()
+    }
+
+    #[test]
+    fn incremental_search_matches_full_search() {
+        let mut history = search_history();
+        for query in ["echo", "HeLLo", "he", "exec", "f", "zzz", "e e"] {
+            let mut typed = String::new();
+            for c in query.chars() {
+                typed.push(c);
+                assert_eq!(history.fuzzy_match(&typed).to_vec(), full_search(&history, &typed), "typing {:?}", typed);
+            }
+            while typed.pop().is_some() {
+                assert_eq!(history.fuzzy_match(&typed).to_vec(), full_search(&history, &typed), "deleting to {:?}", typed);
+            }
         }
     }
 
-    pub fn current(&self) -> String {
-        if self.idx < 0 || self.idx as usize >= self.entries.len() {
-            String::new()
-        } else {
-            self.entries[self.idx as usize].clone()
+    #[test]
+    fn search_after_editing_the_middle_of_the_query() {
+        let mut history = search_history();
+        for query in ["ec", "ech", "eh", "e", "ex", "hel", "eel"] {
+            assert_eq!(history.fuzzy_match(query).to_vec(), full_search(&history, query), "query {:?}", query);
         }
     }
 
-    pub fn reset(&mut self) {
-        self.idx = -1;
+    #[test]
+    fn search_is_refreshed_when_entries_change() {
+        let mut history = search_history();
+        assert!(!history.fuzzy_match("hel").is_empty());
+        history.remove_command("help");
+        history.push_entry("shell".to_string(), String::new(), 0);
+        assert_eq!(history.fuzzy_match("hel").to_vec(), full_search(&history, "hel"));
+        assert_eq!(history.fuzzy_match("").to_vec(), full_search(&history, ""));
+    }
+
+    #[test]
+    fn cursor_wraps() {
+        let history = history_of(&["a", "b", "c"]);
+        let mut cursor = HistoryCursor::new();
+        let older: Vec<String> = (0..5).map(|_| cursor.prev(&history)).collect();
+        assert_eq!(older, ["c", "b", "a", "", "c"]);
+
+        let mut cursor = HistoryCursor::new();
+        let newer: Vec<String> = (0..5).map(|_| cursor.next(&history)).collect();
+        assert_eq!(newer, ["a", "b", "c", "", "a"]);
+
+        let mut empty = HistoryCursor::new();
+        assert_eq!(empty.prev(&history_of(&[])), "");
+    }
+
+    #[test]
+    fn cursor_resets_after_push_entry() {
+        let mut history = history_of(&["a", "b", "c"]);
+        let mut cursor = HistoryCursor::new();
+        cursor.prev(&history);
+        cursor.prev(&history);
+        history.push_entry("d".to_string(), String::new(), 0);
+        assert_eq!(cursor.prev(&history), "d");
+        assert_eq!(cursor.prev(&history), "c");
+    }
+
+    #[test]
+    fn cursor_resets_after_remove_command() {
+        let mut history = history_of(&["a", "b", "c"]);
+        let mut cursor = HistoryCursor::new();
+        cursor.prev(&history);
+        cursor.prev(&history);
+        assert!(history.remove_command("c"));
+        assert_eq!(cursor.prev(&history), "b");
+        assert_eq!(cursor.prev(&history), "a");
+        assert!(!history.remove_command("c"));
+    }
+
+    #[test]
+    fn match_positions_follow_the_score() {
+        let plain = entry("print hello");
+        assert_eq!(match_positions("ph", &plain), vec![0, 6]);
+        assert_eq!(match_positions("hp", &plain), Vec::<usize>::new());
+
+        // `İ` lowercases to two chars; both map back to the same command char.
+        let dotted = entry("İx");
+        assert!(compute_match_score("ix", &dotted.lowercase) > 0);
+        assert_eq!(match_positions("ix", &dotted), vec![0, 1]);
+        assert_eq!(match_positions("x", &dotted), vec![1]);
     }
 }
@@ -6,23 +6,130 @@ use godot::global::PropertyHint;
 use godot::prelude::*;
 use std::collections::HashMap;
 
+use crate::command_history::{DuplicateMode, DEFAULT_HISTORY_FILE};
+use crate::output_buffer::{LinePrefix, LogLevel};
+
 const S: &str = "addons/tiny_console";
 
+/// Where the console panel is placed.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Dock {
+    Top,
+    Bottom,
+    Left,
+    Right,
+    /// A separate OS window.
+    Window,
+}
+
+impl Dock {
+    /// Dock names in order, as used by the ENUM project setting hint.
+    pub const SETTING_VALUES: &str = "top,bottom,left,right,window";
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Dock::Top => "top",
+            Dock::Bottom => "bottom",
+            Dock::Left => "left",
+            Dock::Right => "right",
+            Dock::Window => "window",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "top" => Some(Dock::Top),
+            "bottom" => Some(Dock::Bottom),
+            "left" => Some(Dock::Left),
+            "right" => Some(Dock::Right),
+            "window" => Some(Dock::Window),
+            _ => None,
+        }
+    }
+
+    /// True for the left and right docks, where the panel is sized by width.
+    pub fn is_side(self) -> bool {
+        matches!(self, Dock::Left | Dock::Right)
+    }
+}
+
+/// What happens to the game while the console is open.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum PauseMode {
+    /// The game keeps running.
+    None,
+    Pause,
+    /// The scene tree is paused, but the physics servers keep simulating: rigid bodies keep
+    /// moving, while `_physics_process` of pausable nodes (e.g. a script moving a
+    /// `CharacterBody2D`) is skipped like any other processing.
+    PauseKeepPhysicsServers,
+    /// The game runs at `slow_motion_scale` of its speed.
+    SlowMotion,
+}
+
+impl PauseMode {
+    /// Mode names in order, as used by the ENUM project setting hint.
+    pub const SETTING_VALUES: &str = "none,pause,pause_keep_physics_servers,slow_motion";
+
+    pub fn name(self) -> &'static str {
+        match self {
+            PauseMode::None => "none",
+            PauseMode::Pause => "pause",
+            PauseMode::PauseKeepPhysicsServers => "pause_keep_physics_servers",
+            PauseMode::SlowMotion => "slow_motion",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "none" => Some(PauseMode::None),
+            "pause" => Some(PauseMode::Pause),
+            "pause_keep_physics_servers" => Some(PauseMode::PauseKeepPhysicsServers),
+            "slow_motion" => Some(PauseMode::SlowMotion),
+            _ => None,
+        }
+    }
+}
+
+#[derive(PartialEq)]
 pub struct ConsoleOptions {
     // main
     pub aliases: HashMap<String, String>,
     pub disable_in_release_build: bool,
     pub enable_in_editor: bool,
-    pub print_to_stdout: bool,
-    pub pause_when_open: bool,
+    pub pause_mode: String,
+    /// `Engine.time_scale` while the console is open in the `slow_motion` pause mode.
+    pub slow_motion_scale: f32,
     pub commands_disabled_in_release: Vec<String>,
 
     // appearance
     pub custom_theme: String,
+    pub dock: String,
+    /// Share of the viewport height the panel covers in the top and bottom docks.
     pub height_ratio: f32,
+    /// Share of the viewport width the panel covers in the left and right docks.
+    pub width_ratio: f32,
     pub open_speed: f32,
     pub opacity: f32,
     pub sparse_mode: bool,
+    pub scrollback_lines: i32,
+    pub line_prefix: String,
+
+    // logging
+    pub min_log_level: String,
+    /// Mask of levels mirrored to stdout (see `LogLevel::bit`).
+    pub stdout_levels: i32,
+    pub capture_engine_output: bool,
+    pub session_log_file: String,
+    pub session_log_max_size_kb: i32,
+    pub session_log_max_files: i32,
+
+    // overlay
+    pub overlay_enabled: bool,
+    pub overlay_lines: i32,
+    /// Seconds each overlay line stays on screen.
+    pub overlay_duration: f32,
+    pub overlay_min_level: String,
 
     // greet
     pub greet_user: bool,
@@ -31,7 +138,12 @@ pub struct ConsoleOptions {
 
     // history
     pub persist_history: bool,
+    pub history_file: String,
     pub history_lines: i32,
+    pub history_ignore_space: bool,
+    pub history_duplicates: String,
+    pub history_ignore_failed: bool,
+    pub history_ignore_patterns: Vec<String>,
 
     // autocomplete
     pub autocomplete_use_history_with_matches: bool,
@@ -52,22 +164,43 @@ impl Default for ConsoleOptions {
             aliases,
             disable_in_release_build: false,
             enable_in_editor: false,
-            print_to_stdout: false,
-            pause_when_open: true,
+            pause_mode: "pause".into(),
+            slow_motion_scale: 0.1,
             commands_disabled_in_release: vec!["eval".into()],
 
             custom_theme: "res://addons/tiny_console/res/default_theme.tres".into(),
+            dock: "top".into(),
             height_ratio: 0.5,
+            width_ratio: 0.4,
             open_speed: 5.0,
             opacity: 1.0,
             sparse_mode: false,
+            scrollback_lines: 5000,
+            line_prefix: "none".into(),
+
+            min_log_level: "debug".into(),
+            stdout_levels: 0,
+            capture_engine_output: true,
+            session_log_file: String::new(),
+            session_log_max_size_kb: 1024,
+            session_log_max_files: 3,
+
+            overlay_enabled: false,
+            overlay_lines: 5,
+            overlay_duration: 5.0,
+            overlay_min_level: "info".into(),
 
             greet_user: true,
             greeting_message: "Tiny Console".into(),
             greet_using_ascii_art: true,
 
             persist_history: true,
+            history_file: DEFAULT_HISTORY_FILE.into(),
             history_lines: 1000,
+            history_ignore_space: false,
+            history_duplicates: "erase_previous".into(),
+            history_ignore_failed: false,
+            history_ignore_patterns: Vec::new(),
 
             autocomplete_use_history_with_matches: true,
 
@@ -95,8 +228,20 @@ impl ConsoleOptions {
             self.disable_in_release_build,
         );
         define_bool(&mut ps, &key("enable_in_editor"), self.enable_in_editor);
-        define_bool(&mut ps, &key("print_to_stdout"), self.print_to_stdout);
-        define_bool(&mut ps, &key("pause_when_open"), self.pause_when_open);
+        define_string(
+            &mut ps,
+            &key("pause_mode"),
+            &self.pause_mode,
+            PropertyHint::ENUM,
+            PauseMode::SETTING_VALUES,
+        );
+        define_float(
+            &mut ps,
+            &key("slow_motion_scale"),
+            self.slow_motion_scale,
+            PropertyHint::RANGE,
+            "0.01,1.0,0.01",
+        );
 
         // aliases (Dictionary)
         {
@@ -116,27 +261,11 @@ impl ConsoleOptions {
             add_property_info(&mut ps, &k, VariantType::DICTIONARY, PropertyHint::NONE, "");
         }
 
-        // commands_disabled_in_release (PackedStringArray)
-        {
-            let k = key("commands_disabled_in_release");
-            let arr: PackedStringArray = self
-                .commands_disabled_in_release
-                .iter()
-                .map(|s| GString::from(s.as_str()))
-                .collect();
-            let default_val = arr.to_variant();
-            if !ps.has_setting(&k) {
-                ps.set_setting(&k, &default_val);
-            }
-            ps.set_initial_value(&k, &default_val);
-            add_property_info(
-                &mut ps,
-                &k,
-                VariantType::PACKED_STRING_ARRAY,
-                PropertyHint::NONE,
-                "",
-            );
-        }
+        define_string_array(
+            &mut ps,
+            &key("commands_disabled_in_release"),
+            &self.commands_disabled_in_release,
+        );
 
         // -- appearance --
         define_string(
@@ -146,6 +275,13 @@ impl ConsoleOptions {
             PropertyHint::FILE,
             "*.tres",
         );
+        define_string(
+            &mut ps,
+            &key("appearance/dock"),
+            &self.dock,
+            PropertyHint::ENUM,
+            Dock::SETTING_VALUES,
+        );
         define_float(
             &mut ps,
             &key("appearance/height_ratio"),
@@ -153,6 +289,13 @@ impl ConsoleOptions {
             PropertyHint::RANGE,
             "0.1,1.0,0.05",
         );
+        define_float(
+            &mut ps,
+            &key("appearance/width_ratio"),
+            self.width_ratio,
+            PropertyHint::RANGE,
+            "0.1,1.0,0.05",
+        );
         define_float(
             &mut ps,
             &key("appearance/open_speed"),
@@ -168,6 +311,86 @@ impl ConsoleOptions {
             "0.0,1.0,0.05",
         );
         define_bool(&mut ps, &key("appearance/sparse_mode"), self.sparse_mode);
+        define_int(
+            &mut ps,
+            &key("appearance/scrollback_lines"),
+            self.scrollback_lines,
+            PropertyHint::RANGE,
+            "100,100000,100",
+        );
+        define_string(
+            &mut ps,
+            &key("appearance/line_prefix"),
+            &self.line_prefix,
+            PropertyHint::ENUM,
+            LinePrefix::SETTING_VALUES,
+        );
+
+        // -- logging --
+        define_string(
+            &mut ps,
+            &key("logging/min_level"),
+            &self.min_log_level,
+            PropertyHint::ENUM,
+            LogLevel::SETTING_VALUES,
+        );
+        define_int(
+            &mut ps,
+            &key("logging/stdout_levels"),
+            self.stdout_levels,
+            PropertyHint::FLAGS,
+            LogLevel::SETTING_VALUES,
+        );
+        define_bool(
+            &mut ps,
+            &key("logging/capture_engine_output"),
+            self.capture_engine_output,
+        );
+        define_string(
+            &mut ps,
+            &key("logging/session_log_file"),
+            &self.session_log_file,
+            PropertyHint::SAVE_FILE,
+            "*.log",
+        );
+        define_int(
+            &mut ps,
+            &key("logging/session_log_max_size_kb"),
+            self.session_log_max_size_kb,
+            PropertyHint::RANGE,
+            "0,102400,64",
+        );
+        define_int(
+            &mut ps,
+            &key("logging/session_log_max_files"),
+            self.session_log_max_files,
+            PropertyHint::RANGE,
+            "0,100,1",
+        );
+
+        // -- overlay --
+        define_bool(&mut ps, &key("overlay/enabled"), self.overlay_enabled);
+        define_int(
+            &mut ps,
+            &key("overlay/lines"),
+            self.overlay_lines,
+            PropertyHint::RANGE,
+            "1,20,1",
+        );
+        define_float(
+            &mut ps,
+            &key("overlay/duration"),
+            self.overlay_duration,
+            PropertyHint::RANGE,
+            "1.0,60.0,0.5",
+        );
+        define_string(
+            &mut ps,
+            &key("overlay/min_level"),
+            &self.overlay_min_level,
+            PropertyHint::ENUM,
+            LogLevel::SETTING_VALUES,
+        );
 
         // -- greet --
         define_bool(&mut ps, &key("greet/greet_user"), self.greet_user);
@@ -190,6 +413,13 @@ impl ConsoleOptions {
             &key("history/persist_history"),
             self.persist_history,
         );
+        define_string(
+            &mut ps,
+            &key("history/history_file"),
+            &self.history_file,
+            PropertyHint::SAVE_FILE,
+            "*.log",
+        );
         define_int(
             &mut ps,
             &key("history/history_lines"),
@@ -197,6 +427,28 @@ impl ConsoleOptions {
             PropertyHint::RANGE,
             "10,10000,10",
         );
+        define_bool(
+            &mut ps,
+            &key("history/ignore_space"),
+            self.history_ignore_space,
+        );
+        define_string(
+            &mut ps,
+            &key("history/duplicates"),
+            &self.history_duplicates,
+            PropertyHint::ENUM,
+            DuplicateMode::SETTING_VALUES,
+        );
+        define_bool(
+            &mut ps,
+            &key("history/ignore_failed"),
+            self.history_ignore_failed,
+        );
+        define_string_array(
+            &mut ps,
+            &key("history/ignore_patterns"),
+            &self.history_ignore_patterns,
+        );
 
         // -- autocomplete --
         define_bool(
@@ -227,8 +479,13 @@ impl ConsoleOptions {
         // -- main --
         self.disable_in_release_build = get_bool(&ps, &key("disable_in_release_build"));
         self.enable_in_editor = get_bool(&ps, &key("enable_in_editor"));
-        self.print_to_stdout = get_bool(&ps, &key("print_to_stdout"));
-        self.pause_when_open = get_bool(&ps, &key("pause_when_open"));
+        self.pause_mode = get_string(&ps, &key("pause_mode"));
+        self.slow_motion_scale = get_float(&ps, &key("slow_motion_scale"));
+        // Projects configured before pause modes could only turn pausing off
+        let legacy_pause = key("pause_when_open");
+        if ps.has_setting(&legacy_pause) && !get_bool(&ps, &legacy_pause) {
+            self.pause_mode = PauseMode::None.name().into();
+        }
 
         // aliases
         {
@@ -245,23 +502,39 @@ impl ConsoleOptions {
             }
         }
 
-        // commands_disabled_in_release
-        {
-            let val = ps.get_setting(&key("commands_disabled_in_release"));
-            if let Ok(arr) = val.try_to::<PackedStringArray>() {
-                self.commands_disabled_in_release.clear();
-                for s in arr.as_slice() {
-                    self.commands_disabled_in_release.push(s.to_string());
-                }
-            }
+        if let Some(commands) = get_string_array(&ps, &key("commands_disabled_in_release")) {
+            self.commands_disabled_in_release = commands;
         }
 
         // -- appearance --
         self.custom_theme = get_string(&ps, &key("appearance/custom_theme"));
+        self.dock = get_string(&ps, &key("appearance/dock"));
         self.height_ratio = get_float(&ps, &key("appearance/height_ratio"));
+        self.width_ratio = get_float(&ps, &key("appearance/width_ratio"));
         self.open_speed = get_float(&ps, &key("appearance/open_speed"));
         self.opacity = get_float(&ps, &key("appearance/opacity"));
         self.sparse_mode = get_bool(&ps, &key("appearance/sparse_mode"));
+        self.scrollback_lines = get_int(&ps, &key("appearance/scrollback_lines"));
+        self.line_prefix = get_string(&ps, &key("appearance/line_prefix"));
+
+        // -- logging --
+        self.min_log_level = get_string(&ps, &key("logging/min_level"));
+        self.stdout_levels = get_int(&ps, &key("logging/stdout_levels"));
+        self.capture_engine_output = get_bool(&ps, &key("logging/capture_engine_output"));
+        self.session_log_file = get_string(&ps, &key("logging/session_log_file"));
+        self.session_log_max_size_kb = get_int(&ps, &key("logging/session_log_max_size_kb"));
+        self.session_log_max_files = get_int(&ps, &key("logging/session_log_max_files"));
+        // Projects configured before per-level mirroring used a single flag for all levels
+        let legacy_stdout = key("print_to_stdout");
+        if ps.has_setting(&legacy_stdout) && get_bool(&ps, &legacy_stdout) {
+            self.stdout_levels = LogLevel::ALL_BITS as i32;
+        }
+
+        // -- overlay --
+        self.overlay_enabled = get_bool(&ps, &key("overlay/enabled"));
+        self.overlay_lines = get_int(&ps, &key("overlay/lines"));
+        self.overlay_duration = get_float(&ps, &key("overlay/duration"));
+        self.overlay_min_level = get_string(&ps, &key("overlay/min_level"));
 
         // -- greet --
         self.greet_user = get_bool(&ps, &key("greet/greet_user"));
@@ -270,7 +543,14 @@ impl ConsoleOptions {
 
         // -- history --
         self.persist_history = get_bool(&ps, &key("history/persist_history"));
+        self.history_file = get_string(&ps, &key("history/history_file"));
         self.history_lines = get_int(&ps, &key("history/history_lines"));
+        self.history_ignore_space = get_bool(&ps, &key("history/ignore_space"));
+        self.history_duplicates = get_string(&ps, &key("history/duplicates"));
+        self.history_ignore_failed = get_bool(&ps, &key("history/ignore_failed"));
+        if let Some(patterns) = get_string_array(&ps, &key("history/ignore_patterns")) {
+            self.history_ignore_patterns = patterns;
+        }
 
         // -- autocomplete --
         self.autocomplete_use_history_with_matches =
@@ -299,8 +579,8 @@ fn add_property_info(
 ) {
     let mut info = VarDictionary::new();
     info.set("name", name.to_variant());
-    info.set("type", (variant_type.ord() as i32).to_variant());
-    info.set("hint", (hint.ord() as i32).to_variant());
+    info.set("type", variant_type.ord().to_variant());
+    info.set("hint", hint.ord().to_variant());
     info.set("hint_string", GString::from(hint_string).to_variant());
     ps.add_property_info(&info);
 }
@@ -359,6 +639,22 @@ fn define_string(
     add_property_info(ps, name, VariantType::STRING, hint, hint_string);
 }
 
+fn define_string_array(ps: &mut Gd<ProjectSettings>, name: &GString, default: &[String]) {
+    let arr: PackedStringArray = default.iter().map(|s| GString::from(s.as_str())).collect();
+    let val = arr.to_variant();
+    if !ps.has_setting(name) {
+        ps.set_setting(name, &val);
+    }
+    ps.set_initial_value(name, &val);
+    add_property_info(
+        ps,
+        name,
+        VariantType::PACKED_STRING_ARRAY,
+        PropertyHint::NONE,
+        "",
+    );
+}
+
 fn get_bool(ps: &Gd<ProjectSettings>, name: &GString) -> bool {
     ps.get_setting(name).to::<bool>()
 }
@@ -374,3 +670,8 @@ fn get_int(ps: &Gd<ProjectSettings>, name: &GString) -> i32 {
 fn get_string(ps: &Gd<ProjectSettings>, name: &GString) -> String {
     ps.get_setting(name).to::<GString>().to_string()
 }
+
+fn get_string_array(ps: &Gd<ProjectSettings>, name: &GString) -> Option<Vec<String>> {
+    let arr = ps.get_setting(name).try_to::<PackedStringArray>().ok()?;
+    Some(arr.as_slice().iter().map(|s| s.to_string()).collect())
+}
@@ -0,0 +1,99 @@
+/// EngineLogger: Custom engine Logger that captures `print()`, `printerr()`, `push_warning()`,
+/// `push_error()` and script errors so they appear live in the console.
+/// Logger callbacks can run on any thread, so they only push raw records to a mutex-guarded
+/// queue; TinyConsole drains and formats it on the main thread once per frame.
+use godot::classes::logger::ErrorType;
+use godot::classes::{ILogger, Logger, ScriptBacktrace};
+use godot::prelude::*;
+use std::cell::Cell;
+use std::sync::Mutex;
+
+/// Records are dropped beyond this count if nothing drains the queue.
+const MAX_QUEUED: usize = 1000;
+
+/// A message captured from the engine.
+pub enum EngineLog {
+    /// Output of `print()` (`error == false`) or `printerr()` (`error == true`).
+    Message { text: String, error: bool },
+    /// An engine, script or shader error or warning, with its source location.
+    Error {
+        error_type: ErrorType,
+        message: String,
+        function: String,
+        file: String,
+        line: i32,
+    },
+}
+
+static QUEUE: Mutex<Vec<EngineLog>> = Mutex::new(Vec::new());
+
+thread_local! {
+    static MIRRORING: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Runs `f` (which prints to stdout) without capturing what it prints.
+/// Used for the console's own stdout mirroring, which would otherwise be printed twice.
+pub fn without_capture(f: impl FnOnce()) {
+    MIRRORING.with(|m| m.set(true));
+    f();
+    MIRRORING.with(|m| m.set(false));
+}
+
+/// Takes all records captured since the last call, oldest first.
+pub fn take_captured() -> Vec<EngineLog> {
+    match QUEUE.lock() {
+        Ok(mut queue) => std::mem::take(&mut *queue),
+        Err(_) => Vec::new(),
+    }
+}
+
+fn enqueue(record: EngineLog) {
+    if MIRRORING.with(|m| m.get()) {
+        return;
+    }
+    if let Ok(mut queue) = QUEUE.lock() {
+        if queue.len() < MAX_QUEUED {
+            queue.push(record);
+        }
+    }
+}
+
+#[derive(GodotClass)]
+#[class(base=Logger)]
+pub struct EngineLogger {
+    base: Base<Logger>,
+}
+
+#[godot_api]
+impl ILogger for EngineLogger {
+    fn init(base: Base<Logger>) -> Self {
+        Self { base }
+    }
+
+    fn log_message(&mut self, message: GString, error: bool) {
+        let text = message.to_string();
+        let text = text.strip_suffix('\n').unwrap_or(&text).to_string();
+        enqueue(EngineLog::Message { text, error });
+    }
+
+    fn log_error(
+        &mut self,
+        function: GString,
+        file: GString,
+        line: i32,
+        code: GString,
+        rationale: GString,
+        _editor_notify: bool,
+        error_type: i32,
+        _script_backtraces: Array<Gd<ScriptBacktrace>>,
+    ) {
+        let message = if rationale.is_empty() { code } else { rationale };
+        enqueue(EngineLog::Error {
+            error_type: ErrorType::try_from_ord(error_type).unwrap_or(ErrorType::ERROR),
+            message: message.to_string(),
+            function: function.to_string(),
+            file: file.to_string(),
+            line,
+        });
+    }
+}
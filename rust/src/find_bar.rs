@@ -0,0 +1,178 @@
+/// FindBar: Find-in-output bar shown above the console output (Ctrl+F).
+/// Emits signals for query changes, match navigation and the filter toggle;
+/// TinyConsole applies them to the output and reports the match count back.
+use godot::classes::control::SizeFlags;
+use godot::classes::{Button, CheckBox, HBoxContainer, IHBoxContainer, InputEvent, InputEventKey, InputMap, Label, LineEdit};
+use godot::global::Key;
+use godot::prelude::*;
+
+#[derive(GodotClass)]
+#[class(base=HBoxContainer)]
+pub struct FindBar {
+    base: Base<HBoxContainer>,
+
+    query_edit: Option<Gd<LineEdit>>,
+    count_label: Option<Gd<Label>>,
+    filter_toggle: Option<Gd<CheckBox>>,
+}
+
+#[godot_api]
+impl FindBar {
+    /// Emitted when the find query is edited.
+    #[signal]
+    fn query_changed(query: GString);
+
+    /// Emitted to move to the next (`forward`) or previous match.
+    #[signal]
+    fn match_step_requested(forward: bool);
+
+    /// Emitted when "only matching lines" is toggled.
+    #[signal]
+    fn filter_toggled(enabled: bool);
+
+    /// Emitted after the bar hides itself.
+    #[signal]
+    fn closed();
+
+    #[func]
+    fn on_query_text_changed(&mut self, text: GString) {
+        self.base_mut().emit_signal("query_changed", &[text.to_variant()]);
+    }
+
+    #[func]
+    fn on_previous_pressed(&mut self) {
+        self.base_mut().emit_signal("match_step_requested", &[false.to_variant()]);
+    }
+
+    #[func]
+    fn on_next_pressed(&mut self) {
+        self.base_mut().emit_signal("match_step_requested", &[true.to_variant()]);
+    }
+
+    #[func]
+    fn on_filter_toggled(&mut self, enabled: bool) {
+        self.base_mut().emit_signal("filter_toggled", &[enabled.to_variant()]);
+    }
+
+    #[func]
+    fn on_close_pressed(&mut self) {
+        self.close();
+    }
+}
+
+// Rust-side API used by TinyConsole
+impl FindBar {
+    /// Shows the bar and focuses the query with its text selected.
+    pub fn open(&mut self) {
+        self.base_mut().set_visible(true);
+        if let Some(ref mut edit) = self.query_edit {
+            edit.grab_focus();
+            edit.select_all();
+        }
+    }
+
+    pub fn close(&mut self) {
+        self.base_mut().set_visible(false);
+        self.base_mut().emit_signal("closed", &[]);
+    }
+
+    pub fn query(&self) -> String {
+        self.query_edit.as_ref().map(|e| e.get_text().to_string()).unwrap_or_default()
+    }
+
+    pub fn is_filtering(&self) -> bool {
+        self.filter_toggle.as_ref().is_some_and(|t| t.is_pressed())
+    }
+
+    /// Shows the 1-based index of the current match and the total count.
+    pub fn set_match_info(&mut self, current: Option<usize>, total: usize) {
+        let text = format!("{}/{}", current.map_or(0, |i| i + 1), total);
+        if let Some(ref mut label) = self.count_label {
+            label.set_text(text.as_str());
+        }
+    }
+}
+
+#[godot_api]
+impl IHBoxContainer for FindBar {
+    fn init(base: Base<HBoxContainer>) -> Self {
+        Self {
+            base,
+            query_edit: None,
+            count_label: None,
+            filter_toggle: None,
+        }
+    }
+
+    fn ready(&mut self) {
+        let this = self.to_gd();
+
+        let mut edit = LineEdit::new_alloc();
+        edit.set_placeholder("Find in output");
+        edit.set_h_size_flags(SizeFlags::EXPAND_FILL);
+        edit.connect("text_changed", &Callable::from_object_method(&this, "on_query_text_changed"));
+        self.base_mut().add_child(&edit);
+
+        let mut label = Label::new_alloc();
+        label.set_text("0/0");
+        self.base_mut().add_child(&label);
+
+        for (text, tooltip, method) in [
+            ("<", "Previous match (Shift+Enter)", "on_previous_pressed"),
+            (">", "Next match (Enter)", "on_next_pressed"),
+        ] {
+            let mut button = Button::new_alloc();
+            button.set_text(text);
+            button.set_tooltip_text(tooltip);
+            button.set_flat(true);
+            button.connect("pressed", &Callable::from_object_method(&this, method));
+            self.base_mut().add_child(&button);
+        }
+
+        let mut toggle = CheckBox::new_alloc();
+        toggle.set_text("Only matching");
+        toggle.set_tooltip_text("Show only lines containing the query");
+        toggle.connect("toggled", &Callable::from_object_method(&this, "on_filter_toggled"));
+        self.base_mut().add_child(&toggle);
+
+        let mut close = Button::new_alloc();
+        close.set_text("x");
+        close.set_tooltip_text("Close (Esc)");
+        close.set_flat(true);
+        close.connect("pressed", &Callable::from_object_method(&this, "on_close_pressed"));
+        self.base_mut().add_child(&close);
+
+        self.query_edit = Some(edit);
+        self.count_label = Some(label);
+        self.filter_toggle = Some(toggle);
+    }
+
+    fn input(&mut self, event: Gd<InputEvent>) {
+        if !self.query_edit.as_ref().is_some_and(|e| e.has_focus()) {
+            return;
+        }
+
+        // Consume the console toggle key so it doesn't get typed into the query
+        if InputMap::singleton().has_action("tiny_console_toggle") && event.is_action("tiny_console_toggle") {
+            self.base_mut().get_viewport().unwrap().set_input_as_handled();
+            return;
+        }
+
+        let Ok(key_event) = event.try_cast::<InputEventKey>() else {
+            return;
+        };
+        if !key_event.is_pressed() {
+            return;
+        }
+        let keycode = key_event.get_keycode();
+        if keycode == Key::ENTER || keycode == Key::KP_ENTER {
+            let forward = !key_event.is_shift_pressed();
+            self.base_mut().emit_signal("match_step_requested", &[forward.to_variant()]);
+        } else if keycode == Key::ESCAPE {
+            self.close();
+        } else {
+            return;
+        }
+        self.base_mut().get_viewport().unwrap().set_input_as_handled();
+    }
+}
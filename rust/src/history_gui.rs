@@ -1,30 +1,41 @@
 /// HistoryGui: Fuzzy search UI for command history (fzf-like).
-/// Shows matching history entries as a scrollable list of labels.
-use godot::classes::control::{LayoutPreset, SizeFlags};
+/// Shows matching history entries as a scrollable list with matched characters highlighted,
+/// each entry's run count and last-used time, and a preview line for the selected entry.
+use godot::classes::control::{LayoutPreset, MouseFilter, SizeFlags};
+use godot::classes::notify::ControlNotification;
+use godot::classes::text_server::AutowrapMode;
 use godot::classes::{
-    IPanel, InputEvent, InputEventKey, InputEventMouseButton, Label, Panel, StyleBoxFlat,
+    IPanel, InputEvent, InputEventKey, InputEventMouseButton, Panel, RichTextLabel, StyleBoxFlat,
     VScrollBar,
 };
 use godot::global::{Key, MouseButton};
 use godot::prelude::*;
 
+use crate::command_history::{self, HistoryEntry};
+use crate::util;
+
 #[derive(GodotClass)]
 #[class(base=Panel)]
 pub struct HistoryGui {
     base: Base<Panel>,
 
-    history_labels: Vec<Gd<Label>>,
+    history_labels: Vec<Gd<RichTextLabel>>,
+    preview_label: Option<Gd<RichTextLabel>>,
     scroll_bar: Option<Gd<VScrollBar>>,
     scroll_bar_width: i32,
-    last_highlighted_label: Option<Gd<Label>>,
+    row_height: f32,
+    last_highlighted_label: Option<Gd<RichTextLabel>>,
 
     command: String,
-    filter_results: Vec<String>,
+    /// Indices of the matching history entries, best match first.
+    filter_results: Vec<usize>,
     display_count: usize,
     offset: usize,
     sub_index: usize,
 
     highlight_color: Color,
+    match_color: Color,
+    meta_color: Color,
 }
 
 #[godot_api]
@@ -32,17 +43,17 @@ impl HistoryGui {
     #[signal]
     fn dummy_signal();
 
-    /// Set the search results externally (called by TinyConsole).
-    #[func]
-    pub fn set_search_results(&mut self, results: PackedStringArray) {
-        self.filter_results.clear();
-        for s in results.as_slice() {
-            self.filter_results.push(s.to_string());
-        }
-        self.reset_indexes();
-        self.update_highlight();
-        self.update_scroll_list();
-    }
+    /// Emitted (deferred) when the user scrolls the results: 1 selects an older match, -1 a newer one.
+    #[signal]
+    fn scroll_requested(step: i32);
+
+    /// Emitted (deferred) when the user presses Delete on a selected entry.
+    #[signal]
+    fn delete_requested();
+
+    /// Emitted (deferred) when the rows were laid out again and need refilling.
+    #[signal]
+    fn layout_changed();
 
     /// Search for the command in the history.
     #[func]
@@ -54,18 +65,6 @@ impl HistoryGui {
         self.command = cmd;
     }
 
-    /// Get the currently selected text.
-    #[func]
-    pub fn get_current_text(&self) -> GString {
-        if !self.history_labels.is_empty() && !self.filter_results.is_empty() {
-            let idx = self.get_current_index();
-            if idx < self.filter_results.len() {
-                return GString::from(self.filter_results[idx].as_str());
-            }
-        }
-        GString::from(self.command.as_str())
-    }
-
     /// Set visibility of the history search panel.
     #[func]
     pub fn set_gui_visibility(&mut self, visible: bool) {
@@ -84,36 +83,70 @@ impl HistoryGui {
         self.command = command.to_string();
     }
 
+    /// Lays out the rows for the panel's size. Rows are filled by the next `set_search_results()`.
     #[func]
     fn on_visibility_changed(&mut self) {
         self.calculate_display_count();
     }
+}
+
+// Rust-side API used by TinyConsole
+impl HistoryGui {
+    /// Set the search results externally (called by TinyConsole).
+    /// `results` index into `entries`; only the rows on screen are formatted.
+    /// The selection is kept if the query didn't change (e.g. after deleting an entry).
+    pub fn set_search_results(&mut self, query: &str, results: Vec<usize>, entries: &[HistoryEntry]) {
+        let same_query = query == self.command;
+        self.command = query.to_string();
+        self.filter_results = results;
+        if same_query {
+            self.clamp_indexes();
+        } else {
+            self.reset_indexes();
+        }
+        self.update_scroll_list(entries);
+    }
 
-    pub fn increment_index(&mut self) {
+    /// Returns the command of the selected entry, if any.
+    pub fn current_command(&self, entries: &[HistoryEntry]) -> Option<String> {
+        self.get_current_row(entries).map(|row| row.command.clone())
+    }
+
+    /// Returns the command of the selected entry, or the search query if nothing is selected.
+    pub fn current_text(&self, entries: &[HistoryEntry]) -> String {
+        match self.get_current_row(entries) {
+            Some(row) => row.command.clone(),
+            None => self.command.clone(),
+        }
+    }
+
+    /// Selects the next older match, scrolling the list if needed.
+    pub fn increment_index(&mut self, entries: &[HistoryEntry]) {
         let current_index = self.get_current_index();
         if current_index + 1 >= self.filter_results.len() {
             return;
         }
         if self.sub_index >= self.display_count.saturating_sub(1) {
             self.offset += 1;
-            self.update_scroll_list();
+            self.update_scroll_list(entries);
         } else {
             self.sub_index += 1;
-            self.update_highlight();
+            self.update_highlight(entries);
         }
     }
 
-    pub fn decrement_index(&mut self) {
+    /// Selects the next newer match, scrolling the list if needed.
+    pub fn decrement_index(&mut self, entries: &[HistoryEntry]) {
         let current_index = self.get_current_index();
         if current_index == 0 {
             return;
         }
         if self.sub_index == 0 {
             self.offset = self.offset.saturating_sub(1);
-            self.update_scroll_list();
+            self.update_scroll_list(entries);
         } else {
             self.sub_index -= 1;
-            self.update_highlight();
+            self.update_highlight(entries);
         }
     }
 }
@@ -124,44 +157,103 @@ impl HistoryGui {
         self.offset + self.sub_index
     }
 
+    fn get_current_row<'a>(&self, entries: &'a [HistoryEntry]) -> Option<&'a HistoryEntry> {
+        self.filter_results.get(self.get_current_index()).and_then(|&idx| entries.get(idx))
+    }
+
     fn reset_indexes(&mut self) {
         self.offset = 0;
         self.sub_index = 0;
     }
 
-    fn update_scroll_list(&mut self) {
-        for i in 0..self.display_count {
-            if i >= self.history_labels.len() {
-                break;
-            }
-            let filter_index = self.offset + i;
-            if filter_index < self.filter_results.len() {
-                self.history_labels[i]
-                    .set_text(&GString::from(self.filter_results[filter_index].as_str()));
-            } else {
-                self.history_labels[i].set_text(&GString::new());
-            }
+    fn clamp_indexes(&mut self) {
+        let len = self.filter_results.len();
+        if self.get_current_index() < len {
+            return;
+        }
+        let last = len.saturating_sub(1);
+        self.sub_index = self.sub_index.min(last);
+        self.offset = last - self.sub_index;
+    }
+
+    fn update_scroll_list(&mut self, entries: &[HistoryEntry]) {
+        for i in 0..self.display_count.min(self.history_labels.len()) {
+            let text = match self.filter_results.get(self.offset + i).and_then(|&idx| entries.get(idx)) {
+                Some(row) => self.format_row(row),
+                None => String::new(),
+            };
+            self.history_labels[i].set_text(&GString::from(text.as_str()));
         }
         self.update_scroll_bar();
-        self.update_highlight();
+        self.update_highlight(entries);
     }
 
-    fn update_highlight(&mut self) {
-        if self.filter_results.is_empty() {
-            return;
+    /// Formats a row as BBCode: the command with matched characters highlighted, followed by its run count and age.
+    fn format_row(&self, row: &HistoryEntry) -> String {
+        let positions = command_history::match_positions(&self.command, row);
+        let mut positions = positions.into_iter().peekable();
+        let match_color = self.match_color.to_html();
+
+        let mut text = String::with_capacity(row.command.len() * 2);
+        for (i, c) in row.command.chars().enumerate() {
+            let escaped = util::bbcode_escape(c.encode_utf8(&mut [0u8; 4]));
+            if positions.peek() == Some(&i) {
+                positions.next();
+                text.push_str(&format!("[color={}][b]{}[/b][/color]", match_color, escaped));
+            } else {
+                text.push_str(&escaped);
+            }
         }
 
-        let mut style = StyleBoxFlat::new_gd();
-        style.set_bg_color(self.highlight_color);
+        let mut meta = format!("{}×", row.run_count);
+        if row.timestamp > 0 {
+            let age = command_history::unix_time_now() - row.timestamp;
+            meta.push_str(&format!("  {} ago", util::format_relative_time(age)));
+        }
+        text.push_str(&format!("  [color={}]{}[/color]", self.meta_color.to_html(), meta));
+        text
+    }
 
+    /// Shows details of the selected entry in the preview line.
+    fn update_preview(&mut self, entries: &[HistoryEntry]) {
+        let text = match self.get_current_row(entries) {
+            Some(row) => {
+                let mut details = Vec::new();
+                if row.timestamp > 0 {
+                    details.push(util::format_local_datetime(row.timestamp));
+                }
+                if !row.scene.is_empty() {
+                    details.push(util::bbcode_escape(&row.scene));
+                }
+                details.push(format!("exit {}", row.exit_code));
+                details.push("[b]Del[/b] to remove".to_string());
+                format!("[color={}]{}[/color]", self.meta_color.to_html(), details.join("  ·  "))
+            }
+            None => String::new(),
+        };
+        if let Some(ref mut preview) = self.preview_label {
+            preview.set_text(&GString::from(text.as_str()));
+        }
+    }
+
+    fn update_highlight(&mut self, entries: &[HistoryEntry]) {
         // Clear previous highlight
         if let Some(ref mut last) = self.last_highlighted_label {
             if last.is_instance_valid() {
                 last.remove_theme_stylebox_override("normal");
             }
         }
+        self.last_highlighted_label = None;
+        self.update_preview(entries);
+
+        if self.filter_results.is_empty() {
+            return;
+        }
+
+        let mut style = StyleBoxFlat::new_gd();
+        style.set_bg_color(self.highlight_color);
 
-        if self.sub_index < self.history_labels.len() && !self.filter_results.is_empty() {
+        if self.sub_index < self.history_labels.len() {
             self.history_labels[self.sub_index].add_theme_stylebox_override("normal", &style);
             self.last_highlighted_label = Some(self.history_labels[self.sub_index].clone());
         }
@@ -179,76 +271,96 @@ impl HistoryGui {
         }
     }
 
+    /// Creates and positions as many row labels as fit the panel. Doesn't fill them, as that
+    /// needs the history entries.
     fn calculate_display_count(&mut self) {
-        if !self.base().is_visible() {
+        if !self.base().is_visible() || self.row_height <= 0.0 {
             return;
         }
 
         let panel_size = self.base().get_size();
-        let max_y = panel_size.y;
-
-        if self.history_labels.is_empty() {
-            return;
-        }
-
-        let label_size_y = self.history_labels[0].get_size().y;
-        if label_size_y <= 0.0 {
-            return;
-        }
         let label_size_x = panel_size.x - self.scroll_bar_width as f32;
 
-        let new_display_count = (max_y / label_size_y) as usize;
-        if new_display_count == 0 || new_display_count <= self.display_count {
-            self.reposition_labels(label_size_x, label_size_y, panel_size.y);
-            return;
-        }
+        // The top row is reserved for the preview line.
+        let new_display_count = ((panel_size.y / self.row_height) as usize).saturating_sub(1);
 
+        // Create additional labels as needed
+        while self.history_labels.len() < new_display_count {
+            let label = self.create_row_label();
+            self.base_mut().add_child(&label);
+            self.history_labels.push(label);
+        }
         self.display_count = new_display_count;
+        self.sub_index = self.sub_index.min(self.display_count.saturating_sub(1));
 
-        // Reposition first label
-        self.history_labels[0].set_position(Vector2::new(0.0, panel_size.y - label_size_y));
-        self.history_labels[0].set_size(Vector2::new(label_size_x, label_size_y));
+        self.reposition_labels(label_size_x, panel_size.y);
 
-        // Create additional labels as needed
-        let labels_needed = self.display_count.saturating_sub(self.history_labels.len());
-        for _ in 0..labels_needed {
-            let mut new_label = Label::new_alloc();
-            new_label.set_v_size_flags(SizeFlags::SHRINK_END);
-            new_label.set_h_size_flags(SizeFlags::EXPAND_FILL);
-
-            let position_offset = (self.history_labels.len() + 1) as f32;
-            new_label.set_position(Vector2::new(
-                0.0,
-                panel_size.y - position_offset * label_size_y,
-            ));
-            new_label.set_size(Vector2::new(label_size_x, label_size_y));
-
-            self.base_mut().add_child(&new_label);
-            self.history_labels.push(new_label);
+        if let Some(ref mut preview) = self.preview_label {
+            preview.set_position(Vector2::ZERO);
+            preview.set_size(Vector2::new(label_size_x, self.row_height));
         }
 
-        self.reposition_labels(label_size_x, label_size_y, panel_size.y);
-
         // Update scroll bar position
         if let Some(ref mut scroll_bar) = self.scroll_bar {
             scroll_bar.set_size(Vector2::new(self.scroll_bar_width as f32, panel_size.y));
             scroll_bar.set_position(Vector2::new(label_size_x, 0.0));
         }
-
-        self.reset_indexes();
-        self.update_highlight();
-        self.update_scroll_list();
     }
 
-    fn reposition_labels(&mut self, label_size_x: f32, label_size_y: f32, panel_height: f32) {
+    /// Stacks row labels from the bottom up (best match at the bottom, next to the input line).
+    fn reposition_labels(&mut self, label_size_x: f32, panel_height: f32) {
+        let row_height = self.row_height;
+        let display_count = self.display_count;
         for (i, label) in self.history_labels.iter_mut().enumerate() {
             let position_offset = (i + 1) as f32;
-            label.set_position(Vector2::new(
-                0.0,
-                panel_height - position_offset * label_size_y,
-            ));
-            label.set_size(Vector2::new(label_size_x, label_size_y));
+            label.set_position(Vector2::new(0.0, panel_height - position_offset * row_height));
+            label.set_size(Vector2::new(label_size_x, row_height));
+            label.set_visible(i < display_count);
+        }
+    }
+
+    /// Reads the row height and colors from the theme.
+    fn cache_theme(&mut self) {
+        // Row height follows the themed font, so rows line up with the output text
+        if let Some(preview) = &self.preview_label {
+            let font = preview.get_theme_font_ex("normal_font").done();
+            let font_size = preview.get_theme_font_size_ex("normal_font_size").done();
+            if let Some(font) = font {
+                self.row_height = font.get_height_ex().font_size(font_size).done();
+            }
         }
+
+        // Try to load colors from theme
+        let panel = self.base().clone();
+        for (name, color) in [
+            ("history_highlight_color", &mut self.highlight_color),
+            ("history_match_color", &mut self.match_color),
+            ("history_meta_color", &mut self.meta_color),
+        ] {
+            if panel.has_theme_color_ex(name).theme_type("ConsoleColors").done() {
+                *color = panel.get_theme_color_ex(name).theme_type("ConsoleColors").done();
+            }
+        }
+    }
+
+    fn create_row_label(&self) -> Gd<RichTextLabel> {
+        let mut label = RichTextLabel::new_alloc();
+        label.set_use_bbcode(true);
+        label.set_scroll_active(false);
+        label.set_autowrap_mode(AutowrapMode::OFF);
+        label.set_clip_contents(true);
+        label.set_mouse_filter(MouseFilter::IGNORE);
+        label.set_v_size_flags(SizeFlags::SHRINK_END);
+        label.set_h_size_flags(SizeFlags::EXPAND_FILL);
+        label
+    }
+
+    /// Emits a signal from input handling. Deferred: the handler updates this panel through a
+    /// fresh borrow, and it needs the console's history entries.
+    fn emit_deferred(&mut self, signal: &str, args: &[Variant]) {
+        let mut call_args = vec![signal.to_variant()];
+        call_args.extend_from_slice(args);
+        self.base_mut().call_deferred("emit_signal", &call_args);
     }
 }
 
@@ -258,8 +370,10 @@ impl IPanel for HistoryGui {
         Self {
             base,
             history_labels: Vec::new(),
+            preview_label: None,
             scroll_bar: None,
             scroll_bar_width: 12,
+            row_height: 0.0,
             last_highlighted_label: None,
             command: "<placeholder>".to_string(),
             filter_results: Vec::new(),
@@ -267,6 +381,8 @@ impl IPanel for HistoryGui {
             offset: 0,
             sub_index: 0,
             highlight_color: Color::from_rgba(1.0, 1.0, 1.0, 0.12),
+            match_color: Color::from_rgba(0.337, 0.62, 1.0, 1.0),
+            meta_color: Color::from_rgba(1.0, 1.0, 1.0, 0.35),
         }
     }
 
@@ -276,32 +392,18 @@ impl IPanel for HistoryGui {
         self.base_mut().set_h_size_flags(SizeFlags::EXPAND_FILL);
         self.base_mut().set_v_size_flags(SizeFlags::EXPAND_FILL);
 
-        // Create first label
-        let mut first_label = Label::new_alloc();
-        first_label.set_v_size_flags(SizeFlags::SHRINK_END);
-        first_label.set_h_size_flags(SizeFlags::EXPAND_FILL);
-        first_label.set_text("<Placeholder>");
-        self.base_mut().add_child(&first_label);
-        self.history_labels.push(first_label);
+        // Create preview label; rows are created once the panel size is known
+        let preview = self.create_row_label();
+        self.base_mut().add_child(&preview);
+
+        self.preview_label = Some(preview);
 
         // Create scroll bar
         let scroll_bar = VScrollBar::new_alloc();
         self.base_mut().add_child(&scroll_bar);
         self.scroll_bar = Some(scroll_bar);
 
-        // Try to load highlight color from theme
-        if self
-            .base()
-            .has_theme_color_ex("history_highlight_color")
-            .theme_type("ConsoleColors")
-            .done()
-        {
-            self.highlight_color = self
-                .base()
-                .get_theme_color_ex("history_highlight_color")
-                .theme_type("ConsoleColors")
-                .done();
-        }
+        self.cache_theme();
 
         // Connect visibility_changed to calculate_display_count
         let this = self.to_gd();
@@ -319,9 +421,9 @@ impl IPanel for HistoryGui {
         // Mouse wheel scrolling
         if let Ok(mouse_event) = event.clone().try_cast::<InputEventMouseButton>() {
             if mouse_event.get_button_index() == MouseButton::WHEEL_UP {
-                self.increment_index();
+                self.emit_deferred("scroll_requested", &[1.to_variant()]);
             } else if mouse_event.get_button_index() == MouseButton::WHEEL_DOWN {
-                self.decrement_index();
+                self.emit_deferred("scroll_requested", &[(-1).to_variant()]);
             }
         }
 
@@ -332,17 +434,34 @@ impl IPanel for HistoryGui {
             }
             let keycode = key_event.get_keycode();
             if keycode == Key::UP {
-                self.increment_index();
+                self.emit_deferred("scroll_requested", &[1.to_variant()]);
                 self.base_mut()
                     .get_viewport()
                     .unwrap()
                     .set_input_as_handled();
             } else if keycode == Key::DOWN {
-                self.decrement_index();
+                self.emit_deferred("scroll_requested", &[(-1).to_variant()]);
                 self.base_mut()
                     .get_viewport()
                     .unwrap()
                     .set_input_as_handled();
+            } else if keycode == Key::DELETE {
+                self.emit_deferred("delete_requested", &[]);
+                self.base_mut()
+                    .get_viewport()
+                    .unwrap()
+                    .set_input_as_handled();
+            }
+        }
+    }
+
+    fn on_notification(&mut self, what: ControlNotification) {
+        // A new font can change how many rows fit, and the rows need the new colors
+        if what == ControlNotification::THEME_CHANGED && self.base().is_node_ready() {
+            self.cache_theme();
+            self.calculate_display_count();
+            if self.base().is_visible() {
+                self.emit_deferred("layout_changed", &[]);
             }
         }
     }
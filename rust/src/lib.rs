@@ -4,9 +4,19 @@ mod command_entry;
 mod command_entry_highlighter;
 mod command_history;
 mod console_options;
+mod engine_logger;
+mod find_bar;
 mod history_gui;
+mod live_line;
+mod output_buffer;
+mod overlay;
+mod session_log;
+mod table;
+mod theme_presets;
 mod tiny_console;
+mod user_prefs;
 mod util;
+mod variant_printer;
 
 use godot::prelude::*;
 
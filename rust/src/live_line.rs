@@ -0,0 +1,81 @@
+/// LiveLine: Handle to an output line that can be rewritten after it was printed.
+/// Returned by `TinyConsole.print_live()` and `TinyConsole.print_progress()`. Updates replace
+/// the line in place instead of appending new lines, so progress reports don't flood the
+/// scrollback. The session log and stdout get the first and the final text only.
+use godot::prelude::*;
+
+use crate::tiny_console::TinyConsole;
+
+#[derive(GodotClass)]
+#[class(no_init, base=RefCounted)]
+pub struct LiveLine {
+    base: Base<RefCounted>,
+    id: u64,
+    text: String,
+    /// Set for progress lines: the label shown before the bar, and the progress in 0..1.
+    progress: Option<(String, f64)>,
+    finished: bool,
+}
+
+impl LiveLine {
+    pub fn create(id: u64, text: String, progress: Option<(String, f64)>, finished: bool) -> Gd<Self> {
+        Gd::from_init_fn(|base| Self { base, id, text, progress, finished })
+    }
+
+    fn apply(&mut self, text: String) {
+        self.text = text;
+        TinyConsole::singleton().bind_mut().update_live_line(self.id, &self.text);
+    }
+
+    fn set_progress_internal(&mut self, label: String, value: f64) {
+        let value = value.clamp(0.0, 1.0);
+        let text = TinyConsole::singleton().bind().format_progress(&label, value);
+        self.progress = Some((label, value));
+        self.apply(text);
+    }
+}
+
+#[godot_api]
+impl LiveLine {
+    /// Replaces the text of the line. On progress lines, replaces the label before the bar.
+    /// Does nothing once the line is finished.
+    #[func]
+    pub fn update(&mut self, text: GString) {
+        if self.finished {
+            return;
+        }
+        match self.progress {
+            Some((_, value)) => self.set_progress_internal(text.to_string(), value),
+            None => self.apply(text.to_string()),
+        }
+    }
+
+    /// Shows a progress bar filled to `value` (0 to 1). Turns a plain live line into a progress
+    /// line labeled with its current text.
+    #[func]
+    pub fn set_progress(&mut self, value: f64) {
+        if self.finished {
+            return;
+        }
+        let label = match self.progress.take() {
+            Some((label, _)) => label,
+            None => self.text.clone(),
+        };
+        self.set_progress_internal(label, value);
+    }
+
+    /// Stops updates and writes the final text to the session log and stdout.
+    #[func]
+    pub fn finish(&mut self) {
+        if self.finished {
+            return;
+        }
+        self.finished = true;
+        TinyConsole::singleton().bind_mut().finish_live_line(&self.text);
+    }
+
+    #[func]
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+}
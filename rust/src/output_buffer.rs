@@ -0,0 +1,669 @@
+/// Output scrollback: structured line records, batched appends, channels, find and filter.
+/// Lines are queued by the print functions and flushed into the output RichTextLabel
+/// once per frame. Each channel keeps its own number of lines; lines past the cap are
+/// dropped by removing only their paragraphs from the label, so the remaining BBCode
+/// is never re-parsed. The label shows one channel or all of them. Changing the
+/// filter or the find query re-renders the retained lines from their records. Lines
+/// changed after being shown are re-rendered in place, along with the lines after them.
+use godot::classes::{Engine, RichTextLabel, Texture2D, Time};
+use godot::prelude::*;
+use std::collections::VecDeque;
+use std::ops::Range;
+
+use crate::util;
+
+/// Severity of an output line.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LogLevel {
+    Debug,
+    Info,
+    Warning,
+    Error,
+}
+
+impl LogLevel {
+    pub const ALL: [LogLevel; 4] = [LogLevel::Debug, LogLevel::Info, LogLevel::Warning, LogLevel::Error];
+
+    /// Level names in order, as used by ENUM and FLAGS project setting hints.
+    pub const SETTING_VALUES: &str = "debug,info,warning,error";
+
+    /// Mask with every level set.
+    pub const ALL_BITS: u8 = 0b1111;
+
+    pub fn name(self) -> &'static str {
+        match self {
+            LogLevel::Debug => "debug",
+            LogLevel::Info => "info",
+            LogLevel::Warning => "warning",
+            LogLevel::Error => "error",
+        }
+    }
+
+    /// Bit of this level in a level mask (matches the order of `SETTING_VALUES`).
+    pub fn bit(self) -> u8 {
+        1 << self as u8
+    }
+
+    /// Parses a level name; `warn` is accepted as a short form of `warning`.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "debug" => Some(LogLevel::Debug),
+            "info" => Some(LogLevel::Info),
+            "warn" | "warning" => Some(LogLevel::Warning),
+            "error" => Some(LogLevel::Error),
+            _ => None,
+        }
+    }
+}
+
+/// Timing information shown before each output line.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum LinePrefix {
+    None,
+    /// Local time of day, `HH:MM:SS.mmm`.
+    WallClock,
+    /// Seconds since the engine started.
+    Uptime,
+    ProcessFrame,
+    PhysicsFrame,
+}
+
+impl LinePrefix {
+    /// Prefix names in order, as used by the ENUM project setting hint.
+    pub const SETTING_VALUES: &str = "none,wall_clock,uptime,process_frame,physics_frame";
+
+    pub fn name(self) -> &'static str {
+        match self {
+            LinePrefix::None => "none",
+            LinePrefix::WallClock => "wall_clock",
+            LinePrefix::Uptime => "uptime",
+            LinePrefix::ProcessFrame => "process_frame",
+            LinePrefix::PhysicsFrame => "physics_frame",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "none" => Some(LinePrefix::None),
+            "wall_clock" => Some(LinePrefix::WallClock),
+            "uptime" => Some(LinePrefix::Uptime),
+            "process_frame" => Some(LinePrefix::ProcessFrame),
+            "physics_frame" => Some(LinePrefix::PhysicsFrame),
+            _ => None,
+        }
+    }
+}
+
+/// A texture shown on an output line. It is added to the label with `add_image`, so it needs no
+/// resource path, and it is released when its line leaves the scrollback.
+#[derive(Clone)]
+pub struct LineImage {
+    pub texture: Gd<Texture2D>,
+    /// Size in pixels; 0 on one axis keeps the aspect ratio, 0 on both shows the texture's own size.
+    pub size: Vector2i,
+}
+
+impl LineImage {
+    pub fn add_to(&self, label: &mut Gd<RichTextLabel>) {
+        label.add_image_ex(&self.texture).width(self.size.x).height(self.size.y).done();
+    }
+}
+
+/// A printed line as kept in the scrollback.
+pub struct OutputLine {
+    /// Line as printed, with BBCode.
+    pub text: String,
+    pub level: LogLevel,
+    /// Index of the line's channel (see `OutputBuffer::channel_id`).
+    pub channel: usize,
+    /// Unix time the line was printed at.
+    pub timestamp: f64,
+    /// Milliseconds since the engine started.
+    pub ticks_msec: u64,
+    pub process_frame: u64,
+    pub physics_frame: u64,
+    /// Text without BBCode tags.
+    plain: String,
+    /// `plain` lowered char by char (so character offsets match), used for find and filter.
+    plain_lower: String,
+    /// Texture shown after the text.
+    image: Option<LineImage>,
+    seq: u64,
+    /// Number of label paragraphs the line occupies: 0 while hidden by the filter,
+    /// otherwise one more than its embedded newlines.
+    paragraphs: i32,
+}
+
+impl OutputLine {
+    fn set_text(&mut self, text: &str) {
+        self.text = text.to_string();
+        self.plain = util::bbcode_strip(text);
+        self.plain_lower = lowercase(&self.plain);
+    }
+}
+
+/// Restricts the output to lines matching a pattern and/or a severity.
+#[derive(Default, Clone)]
+pub struct OutputFilter {
+    /// Case-insensitive substring of the line's plain text, stored lowercase.
+    pattern: Option<String>,
+    pub level: Option<LogLevel>,
+    /// Unix time; earlier lines are hidden.
+    pub since: Option<f64>,
+}
+
+impl OutputFilter {
+    pub fn is_active(&self) -> bool {
+        self.pattern.is_some() || self.level.is_some() || self.since.is_some()
+    }
+
+    /// Sets the substring lines must contain, ignoring case. `None` or an empty pattern clears it.
+    pub fn set_pattern(&mut self, pattern: Option<&str>) {
+        self.pattern = pattern.filter(|p| !p.is_empty()).map(lowercase);
+    }
+
+    fn accepts(&self, line: &OutputLine) -> bool {
+        if self.level.is_some_and(|level| level != line.level) {
+            return false;
+        }
+        if self.since.is_some_and(|since| line.timestamp < since) {
+            return false;
+        }
+        match &self.pattern {
+            Some(pattern) => line.plain_lower.contains(pattern.as_str()),
+            None => true,
+        }
+    }
+}
+
+/// A named stream of output with its own scrollback.
+pub struct Channel {
+    pub name: String,
+    /// Lines printed while another channel was shown.
+    pub unread: usize,
+    /// Retained lines, including queued ones.
+    lines: usize,
+}
+
+pub struct OutputBuffer {
+    lines: VecDeque<OutputLine>,
+    pending: Vec<OutputLine>,
+    next_seq: u64,
+    /// Lines kept per channel.
+    max_lines: usize,
+    channels: Vec<Channel>,
+    /// Channel shown in the label, or `None` for all of them.
+    view: Option<usize>,
+    filter: OutputFilter,
+    /// Mask of levels shown in the output (see `LogLevel::bit`).
+    visible_levels: u8,
+    needs_rebuild: bool,
+    /// Oldest shown line whose text changed since the last flush.
+    dirty_from: Option<u64>,
+
+    /// Lowercase characters of the find query; empty when find is inactive.
+    query: Vec<char>,
+    /// Find matches in visible lines as `(line seq, character offset)`, in output order.
+    matches: Vec<(u64, usize)>,
+    current_match: Option<(u64, usize)>,
+    match_color: Color,
+    current_match_color: Color,
+
+    prefix: LinePrefix,
+    prefix_color: Color,
+    /// Local time zone offset in seconds, cached for wall clock prefixes.
+    time_bias: i64,
+}
+
+impl OutputBuffer {
+    /// Creates an empty scrollback with `default_channel` as channel 0.
+    pub fn new(max_lines: usize, default_channel: &str) -> Self {
+        Self {
+            lines: VecDeque::new(),
+            pending: Vec::new(),
+            next_seq: 0,
+            max_lines: max_lines.max(1),
+            channels: vec![Channel { name: default_channel.to_string(), unread: 0, lines: 0 }],
+            view: None,
+            filter: OutputFilter::default(),
+            visible_levels: LogLevel::ALL_BITS,
+            needs_rebuild: false,
+            dirty_from: None,
+            query: Vec::new(),
+            matches: Vec::new(),
+            current_match: None,
+            match_color: Color::from_rgba(1.0, 0.85, 0.3, 0.25),
+            current_match_color: Color::from_rgba(1.0, 0.6, 0.2, 0.6),
+            prefix: LinePrefix::None,
+            prefix_color: Color::from_rgba(1.0, 1.0, 1.0, 0.35),
+            time_bias: util::local_time_bias(),
+        }
+    }
+
+    /// Queues a line to be appended to a channel on the next flush.
+    pub fn push(&mut self, text: &str, level: LogLevel, channel: usize) {
+        self.push_line(text, None, level, channel);
+    }
+
+    /// Queues a line showing an image to be appended to a channel on the next flush.
+    pub fn push_image(&mut self, image: LineImage, level: LogLevel, channel: usize) {
+        self.push_line("", Some(image), level, channel);
+    }
+
+    fn push_line(&mut self, text: &str, image: Option<LineImage>, level: LogLevel, channel: usize) {
+        let channel = if channel < self.channels.len() { channel } else { 0 };
+        self.channels[channel].lines += 1;
+        if self.view.is_some_and(|view| view != channel) {
+            self.channels[channel].unread += 1;
+        }
+        let plain = util::bbcode_strip(text);
+        self.pending.push(OutputLine {
+            text: text.to_string(),
+            level,
+            channel,
+            timestamp: Time::singleton().get_unix_time_from_system(),
+            ticks_msec: Time::singleton().get_ticks_msec(),
+            process_frame: Engine::singleton().get_process_frames(),
+            physics_frame: Engine::singleton().get_physics_frames(),
+            plain_lower: lowercase(&plain),
+            plain,
+            image,
+            seq: self.next_seq,
+            paragraphs: 0,
+        });
+        self.next_seq += 1;
+    }
+
+    /// Id the next pushed line will get.
+    pub fn next_id(&self) -> u64 {
+        self.next_seq
+    }
+
+    /// Changes the text of a line that is still retained. Takes effect on the next flush.
+    /// Returns false if the line was already dropped.
+    pub fn replace(&mut self, id: u64, text: &str) -> bool {
+        if let Some(line) = self.pending.iter_mut().find(|l| l.seq == id) {
+            line.set_text(text);
+            return true;
+        }
+        let Ok(i) = self.lines.binary_search_by_key(&id, |l| l.seq) else {
+            return false;
+        };
+        self.lines[i].set_text(text);
+        if self.query.is_empty() {
+            self.dirty_from = Some(self.dirty_from.map_or(id, |from| from.min(id)));
+        } else {
+            // Matches of the line changed
+            self.needs_rebuild = true;
+        }
+        true
+    }
+
+    /// Drops all shown and queued lines.
+    pub fn clear(&mut self) {
+        self.lines.clear();
+        self.pending.clear();
+        for channel in &mut self.channels {
+            channel.lines = 0;
+            channel.unread = 0;
+        }
+        self.dirty_from = None;
+        self.matches.clear();
+        self.current_match = None;
+    }
+
+    /// Returns the index of the channel with the given name (case-insensitive), adding it if needed.
+    pub fn channel_id(&mut self, name: &str) -> usize {
+        if let Some(i) = self.find_channel(name) {
+            return i;
+        }
+        self.channels.push(Channel { name: name.to_string(), unread: 0, lines: 0 });
+        self.channels.len() - 1
+    }
+
+    pub fn find_channel(&self, name: &str) -> Option<usize> {
+        self.channels.iter().position(|c| c.name.eq_ignore_ascii_case(name))
+    }
+
+    pub fn channels(&self) -> &[Channel] {
+        &self.channels
+    }
+
+    pub fn view(&self) -> Option<usize> {
+        self.view
+    }
+
+    /// Shows only lines of the given channel, or of all channels for `None`, and marks the
+    /// channel as read. Takes effect on the next flush.
+    pub fn set_view(&mut self, view: Option<usize>) {
+        if let Some(channel) = view.and_then(|i| self.channels.get_mut(i)) {
+            channel.unread = 0;
+        }
+        if view != self.view {
+            self.view = view;
+            self.needs_rebuild = true;
+        }
+    }
+
+    pub fn filter(&self) -> &OutputFilter {
+        &self.filter
+    }
+
+    /// Shows only lines accepted by the filter. Takes effect on the next flush.
+    pub fn set_filter(&mut self, filter: OutputFilter) {
+        self.filter = filter;
+        self.needs_rebuild = true;
+    }
+
+    /// Shows only lines whose level bit is set in `mask`. Takes effect on the next flush.
+    pub fn set_visible_levels(&mut self, mask: u8) {
+        if mask != self.visible_levels {
+            self.visible_levels = mask;
+            self.needs_rebuild = true;
+        }
+    }
+
+    /// Changes the number of lines kept per channel; lines over a lower cap are dropped on the next flush.
+    pub fn set_max_lines(&mut self, max_lines: usize) {
+        let max_lines = max_lines.max(1);
+        if max_lines != self.max_lines {
+            self.max_lines = max_lines;
+            self.needs_rebuild = true;
+        }
+    }
+
+    pub fn prefix(&self) -> LinePrefix {
+        self.prefix
+    }
+
+    /// Shows `prefix` before every line, including lines already printed. Takes effect on the next flush.
+    pub fn set_prefix(&mut self, prefix: LinePrefix) {
+        if prefix != self.prefix {
+            self.prefix = prefix;
+            self.needs_rebuild = true;
+        }
+    }
+
+    pub fn set_prefix_color(&mut self, color: Color) {
+        self.prefix_color = color;
+    }
+
+    pub fn set_match_colors(&mut self, match_color: Color, current_match_color: Color) {
+        self.match_color = match_color;
+        self.current_match_color = current_match_color;
+    }
+
+    /// Highlights all occurrences of `query` (case-insensitive); an empty query ends the search.
+    /// The most recent match becomes current. Takes effect on the next flush.
+    pub fn set_search(&mut self, query: &str) {
+        let query: Vec<char> = query.chars().map(lower_char).collect();
+        if query == self.query {
+            return;
+        }
+        self.query = query;
+        self.current_match = None;
+        self.needs_rebuild = true;
+    }
+
+    /// Moves the current match forward (towards newer lines) or backward, wrapping around.
+    pub fn step_match(&mut self, forward: bool) {
+        if self.matches.is_empty() {
+            return;
+        }
+        let last = self.matches.len() - 1;
+        let idx = match self.current_match_index() {
+            Some(i) if forward => if i == last { 0 } else { i + 1 },
+            Some(i) => if i == 0 { last } else { i - 1 },
+            None if forward => 0,
+            None => last,
+        };
+        self.current_match = Some(self.matches[idx]);
+        self.needs_rebuild = true;
+    }
+
+    /// Returns the index of the current match and the total match count.
+    pub fn match_info(&self) -> (Option<usize>, usize) {
+        (self.current_match_index(), self.matches.len())
+    }
+
+    /// Appends queued lines to the label in a single call and trims the oldest lines over the cap.
+    /// Changed lines are removed from the label together with the lines after them, and
+    /// appended again. Re-renders everything instead if the filter or find state changed.
+    /// Returns false if there was nothing to do.
+    pub fn flush(&mut self, output: &mut Gd<RichTextLabel>) -> bool {
+        if self.pending.is_empty() && !self.needs_rebuild && self.dirty_from.is_none() {
+            return false;
+        }
+
+        let first_added = self.pending.first().map_or(self.next_seq, |l| l.seq);
+        self.lines.extend(self.pending.drain(..));
+        self.trim(output);
+
+        let dirty_from = self.dirty_from.take();
+        if self.needs_rebuild {
+            self.needs_rebuild = false;
+            self.rebuild(output);
+            return true;
+        }
+
+        // Only the new lines are rendered; lines trimmed right away were never appended.
+        let mut start = self.lines.partition_point(|l| l.seq < first_added);
+        if let Some(from) = dirty_from {
+            let first = self.lines.partition_point(|l| l.seq < from);
+            if first < start {
+                if (start - first) * 2 > self.lines.len() {
+                    self.rebuild(output);
+                    return true;
+                }
+                let paragraph: i32 = self.lines.range(..first).map(|l| l.paragraphs).sum();
+                let count: i32 = self.lines.range(first..start).map(|l| l.paragraphs).sum();
+                for _ in 0..count {
+                    output.remove_paragraph(paragraph);
+                }
+                start = first;
+            }
+        }
+        let mut batch = String::new();
+        for i in start..self.lines.len() {
+            if !self.shows(&self.lines[i]) {
+                self.lines[i].paragraphs = 0;
+                continue;
+            }
+            let found = find_in_line(&self.lines[i], &self.query);
+            let seq = self.lines[i].seq;
+            self.matches.extend(found.iter().map(|&pos| (seq, pos)));
+            let rendered = self.render_line(&self.lines[i], &found);
+            self.lines[i].paragraphs = rendered.matches('\n').count() as i32 + 1;
+            append_line(output, &mut batch, &rendered, self.lines[i].image.as_ref());
+        }
+        if !batch.is_empty() {
+            output.append_text(batch.as_str());
+        }
+        if self.query.is_empty() {
+            let line_count = output.get_line_count();
+            output.scroll_to_line(line_count);
+        }
+        true
+    }
+
+    fn shows(&self, line: &OutputLine) -> bool {
+        self.view.is_none_or(|view| view == line.channel) && self.visible_levels & line.level.bit() != 0 && self.filter.accepts(line)
+    }
+
+    /// Returns the plain text of the retained and queued lines of a channel whose ids are in `ids`.
+    pub fn plain_lines(&self, ids: Range<u64>, channel: usize) -> Vec<&str> {
+        self.lines
+            .iter()
+            .chain(self.pending.iter())
+            .filter(|l| ids.contains(&l.seq) && l.channel == channel)
+            .map(|l| l.plain.as_str())
+            .collect()
+    }
+
+    /// Returns all retained and queued lines, one per line, with the current prefix.
+    /// Lines hidden by the filter or level toggles are included.
+    pub fn export(&self, bbcode: bool) -> String {
+        let mut result = String::new();
+        for line in self.lines.iter().chain(self.pending.iter()) {
+            let prefix = self.prefix_text(line);
+            if !prefix.is_empty() {
+                result.push_str(&prefix);
+                result.push(' ');
+            }
+            result.push_str(if bbcode { &line.text } else { &line.plain });
+            result.push('\n');
+        }
+        result
+    }
+
+    fn current_match_index(&self) -> Option<usize> {
+        let current = self.current_match?;
+        self.matches.binary_search(&current).ok()
+    }
+
+    /// Drops the oldest lines of each channel over the cap, removing only their own paragraphs
+    /// from the label.
+    fn trim(&mut self, output: &mut Gd<RichTextLabel>) {
+        let mut excess: Vec<usize> = self.channels.iter().map(|c| c.lines.saturating_sub(self.max_lines)).collect();
+        if excess.iter().all(|&n| n == 0) {
+            return;
+        }
+        let channels = &mut self.channels;
+        let mut trimmed = Vec::new();
+        let mut paragraph = 0;
+        self.lines.retain(|line| {
+            if excess[line.channel] == 0 {
+                paragraph += line.paragraphs;
+                return true;
+            }
+            for _ in 0..line.paragraphs {
+                output.remove_paragraph_ex(paragraph).no_invalidate(true).done();
+            }
+            excess[line.channel] -= 1;
+            channels[line.channel].lines -= 1;
+            trimmed.push(line.seq);
+            false
+        });
+        self.matches.retain(|(seq, _)| trimmed.binary_search(seq).is_err());
+        if self.current_match.is_some_and(|(seq, _)| trimmed.binary_search(&seq).is_ok()) {
+            self.current_match = None;
+        }
+    }
+
+    /// Re-renders all retained lines and scrolls to the current match (or to the end).
+    fn rebuild(&mut self, output: &mut Gd<RichTextLabel>) {
+        self.matches.clear();
+        let mut found_per_line = Vec::with_capacity(self.lines.len());
+        for line in &self.lines {
+            let found = if self.shows(line) { find_in_line(line, &self.query) } else { Vec::new() };
+            self.matches.extend(found.iter().map(|&pos| (line.seq, pos)));
+            found_per_line.push(found);
+        }
+        if self.current_match_index().is_none() {
+            self.current_match = self.matches.last().copied();
+        }
+
+        output.clear();
+        let mut batch = String::new();
+        let mut paragraph = 0;
+        let mut current_paragraph = None;
+        for (i, found) in found_per_line.iter().enumerate() {
+            if !self.shows(&self.lines[i]) {
+                self.lines[i].paragraphs = 0;
+                continue;
+            }
+            if self.current_match.is_some_and(|(seq, _)| seq == self.lines[i].seq) {
+                current_paragraph = Some(paragraph);
+            }
+            let rendered = self.render_line(&self.lines[i], found);
+            let paragraphs = rendered.matches('\n').count() as i32 + 1;
+            self.lines[i].paragraphs = paragraphs;
+            paragraph += paragraphs;
+            append_line(output, &mut batch, &rendered, self.lines[i].image.as_ref());
+        }
+
+        output.append_text(batch.as_str());
+        match current_paragraph {
+            Some(p) => output.scroll_to_paragraph(p),
+            None => {
+                let line_count = output.get_line_count();
+                output.scroll_to_line(line_count);
+            }
+        }
+    }
+
+    fn prefix_text(&self, line: &OutputLine) -> String {
+        match self.prefix {
+            LinePrefix::None => String::new(),
+            LinePrefix::WallClock => util::format_clock_time(line.timestamp, self.time_bias),
+            LinePrefix::Uptime => format!("{:>9.3}", line.ticks_msec as f64 / 1000.0),
+            LinePrefix::ProcessFrame => format!("F{:<7}", line.process_frame),
+            LinePrefix::PhysicsFrame => format!("P{:<7}", line.physics_frame),
+        }
+    }
+
+    fn render_line(&self, line: &OutputLine, found: &[usize]) -> String {
+        let prefix = self.prefix_text(line);
+        let text = self.highlight_matches(line, found);
+        if prefix.is_empty() {
+            text
+        } else {
+            format!("[color={}]{}[/color] {}", self.prefix_color.to_html(), prefix, text)
+        }
+    }
+
+    fn highlight_matches(&self, line: &OutputLine, found: &[usize]) -> String {
+        if found.is_empty() {
+            return line.text.clone();
+        }
+        let len = self.query.len();
+        let ranges: Vec<(usize, usize, Color)> = found
+            .iter()
+            .map(|&pos| {
+                let color = if self.current_match == Some((line.seq, pos)) { self.current_match_color } else { self.match_color };
+                (pos, pos + len, color)
+            })
+            .collect();
+        util::bbcode_highlight(&line.text, &ranges)
+    }
+}
+
+/// Adds a rendered line to the text batched for `append_text`. A line with an image is written
+/// out right away, as the image can only be added to the label after the text before it.
+fn append_line(output: &mut Gd<RichTextLabel>, batch: &mut String, rendered: &str, image: Option<&LineImage>) {
+    batch.push_str(rendered);
+    if let Some(image) = image {
+        output.append_text(batch.as_str());
+        batch.clear();
+        image.add_to(output);
+    }
+    batch.push('\n');
+}
+
+fn lower_char(c: char) -> char {
+    c.to_lowercase().next().unwrap_or(c)
+}
+
+/// Lowercases `text` with `lower_char`, keeping one char per input char.
+fn lowercase(text: &str) -> String {
+    text.chars().map(lower_char).collect()
+}
+
+/// Character offsets of non-overlapping occurrences of `query` in the line's plain text.
+fn find_in_line(line: &OutputLine, query: &[char]) -> Vec<usize> {
+    if query.is_empty() {
+        return Vec::new();
+    }
+    let text: Vec<char> = line.plain_lower.chars().collect();
+    let mut found = Vec::new();
+    let mut i = 0;
+    while i + query.len() <= text.len() {
+        if text[i..i + query.len()] == *query {
+            found.push(i);
+            i += query.len();
+        } else {
+            i += 1;
+        }
+    }
+    found
+}
@@ -0,0 +1,157 @@
+/// Overlay: Recent output lines drawn over the game while the console is closed, in the style of
+/// `con_notifyprint`. Each line fades out a few seconds after it was printed. The overlay ignores
+/// the mouse and never takes focus, so the game keeps all input.
+use godot::classes::control::{LayoutPreset, MouseFilter};
+use godot::classes::{RichTextLabel, Time, VBoxContainer};
+use godot::prelude::*;
+use std::collections::VecDeque;
+
+use crate::output_buffer::{LineImage, LogLevel};
+
+/// Time a line takes to fade out at the end of its duration.
+const FADE_MSEC: u64 = 1000;
+/// Distance from the edges of the viewport, in pixels.
+const MARGIN: f32 = 8.0;
+
+struct Row {
+    /// Id of the output line the row was printed as; a line with embedded newlines has several rows.
+    id: u64,
+    text: String,
+    image: Option<LineImage>,
+    shown_at: u64,
+}
+
+pub struct Overlay {
+    root: Gd<VBoxContainer>,
+    /// One label per shown row, top to bottom.
+    labels: Vec<Gd<RichTextLabel>>,
+    rows: VecDeque<Row>,
+    min_level: LogLevel,
+    duration_msec: u64,
+    /// Set when rows changed since the labels were last filled.
+    dirty: bool,
+}
+
+impl Overlay {
+    /// Creates an overlay of up to `max_lines` rows, each shown for `duration` seconds.
+    pub fn new(max_lines: usize, duration: f32, min_level: LogLevel) -> Self {
+        let mut root = VBoxContainer::new_alloc();
+        root.set_anchors_preset(LayoutPreset::TOP_WIDE);
+        root.set_offset(Side::LEFT, MARGIN);
+        root.set_offset(Side::TOP, MARGIN);
+        root.set_offset(Side::RIGHT, -MARGIN);
+        root.set_mouse_filter(MouseFilter::IGNORE);
+        root.add_theme_constant_override("separation", 0);
+        root.set_visible(false);
+
+        let mut labels = Vec::with_capacity(max_lines);
+        for _ in 0..max_lines {
+            let mut label = RichTextLabel::new_alloc();
+            label.set_use_bbcode(true);
+            label.set_fit_content(true);
+            label.set_scroll_active(false);
+            label.set_mouse_filter(MouseFilter::IGNORE);
+            // Lines are drawn over arbitrary game visuals
+            label.add_theme_color_override("font_shadow_color", Color::from_rgba(0.0, 0.0, 0.0, 0.8));
+            label.add_theme_constant_override("shadow_offset_x", 1);
+            label.add_theme_constant_override("shadow_offset_y", 1);
+            label.set_visible(false);
+            root.add_child(&label);
+            labels.push(label);
+        }
+
+        Self {
+            root,
+            labels,
+            rows: VecDeque::new(),
+            min_level,
+            duration_msec: (duration.max(0.0) * 1000.0) as u64,
+            dirty: false,
+        }
+    }
+
+    pub fn root(&self) -> Gd<VBoxContainer> {
+        self.root.clone()
+    }
+
+    /// True if lines of this level are shown.
+    pub fn accepts(&self, level: LogLevel) -> bool {
+        level >= self.min_level
+    }
+
+    pub fn push(&mut self, id: u64, text: &str) {
+        let now = Time::singleton().get_ticks_msec();
+        for line in text.split('\n') {
+            self.rows.push_back(Row { id, text: line.to_string(), image: None, shown_at: now });
+        }
+        self.drop_oldest_rows();
+    }
+
+    pub fn push_image(&mut self, id: u64, image: LineImage) {
+        let now = Time::singleton().get_ticks_msec();
+        self.rows.push_back(Row { id, text: String::new(), image: Some(image), shown_at: now });
+        self.drop_oldest_rows();
+    }
+
+    fn drop_oldest_rows(&mut self) {
+        while self.rows.len() > self.labels.len() {
+            self.rows.pop_front();
+        }
+        self.dirty = true;
+    }
+
+    /// Replaces the rows of an output line that was changed after being printed, showing them anew.
+    pub fn replace(&mut self, id: u64, text: &str) {
+        let Some(pos) = self.rows.iter().position(|row| row.id == id) else {
+            return;
+        };
+        self.rows.retain(|row| row.id != id);
+        let now = Time::singleton().get_ticks_msec();
+        for (i, line) in text.split('\n').enumerate() {
+            self.rows.insert(pos + i, Row { id, text: line.to_string(), image: None, shown_at: now });
+        }
+        self.drop_oldest_rows();
+    }
+
+    pub fn clear(&mut self) {
+        self.rows.clear();
+        self.dirty = true;
+    }
+
+    /// Drops expired rows and fades the ones about to expire. Called once per frame.
+    /// The overlay is only drawn while `visible` and there is something to show.
+    pub fn update(&mut self, visible: bool) {
+        let now = Time::singleton().get_ticks_msec();
+        let before = self.rows.len();
+        self.rows.retain(|row| now < row.shown_at + self.duration_msec);
+        self.dirty |= self.rows.len() != before;
+
+        let show = visible && !self.rows.is_empty();
+        if self.root.is_visible() != show {
+            self.root.set_visible(show);
+        }
+        if !show && !self.dirty {
+            return;
+        }
+
+        for (i, label) in self.labels.iter_mut().enumerate() {
+            let Some(row) = self.rows.get(i) else {
+                if self.dirty {
+                    label.set_visible(false);
+                }
+                continue;
+            };
+            if self.dirty {
+                label.set_text(&row.text);
+                if let Some(ref image) = row.image {
+                    image.add_to(label);
+                }
+                label.set_visible(true);
+            }
+            let remaining = (row.shown_at + self.duration_msec).saturating_sub(now);
+            let alpha = (remaining as f32 / FADE_MSEC as f32).min(1.0);
+            label.set_modulate(Color::from_rgba(1.0, 1.0, 1.0, alpha));
+        }
+        self.dirty = false;
+    }
+}
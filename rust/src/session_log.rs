@@ -0,0 +1,75 @@
+/// Session log: tees everything printed to the console into a plain-text file, one timestamped line each.
+/// The previous session's file is rotated to `<name>.1.<ext>` (and so on) when a session
+/// starts, and again whenever the file grows past the size limit.
+use godot::classes::file_access::ModeFlags;
+use godot::classes::{DirAccess, FileAccess, Time};
+use godot::prelude::*;
+
+use crate::util;
+
+pub struct SessionLog {
+    path: String,
+    max_bytes: u64,
+    max_files: u32,
+    file: Option<Gd<FileAccess>>,
+    /// Local time zone offset in seconds, cached for line timestamps.
+    time_bias: i64,
+}
+
+impl SessionLog {
+    /// Starts a new session log at `path`, rotating the previous one.
+    /// Returns `None` if the file can't be created.
+    pub fn start(path: &str, max_bytes: u64, max_files: u32) -> Option<Self> {
+        let mut log = Self {
+            path: path.to_string(),
+            max_bytes,
+            max_files,
+            file: None,
+            time_bias: util::local_time_bias(),
+        };
+        log.rotate();
+        log.file.as_ref()?;
+        Some(log)
+    }
+
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Appends a line prefixed with the local time, rotating first if the file is over the size limit.
+    pub fn write_line(&mut self, line: &str) {
+        let time = util::format_clock_time(Time::singleton().get_unix_time_from_system(), self.time_bias);
+        if self.max_bytes > 0 && self.file.as_ref().is_some_and(|f| f.get_position() >= self.max_bytes) {
+            self.rotate();
+        }
+        if let Some(ref mut file) = self.file {
+            file.store_string(&format!("{} {}\n", time, line));
+        }
+    }
+
+    /// Writes buffered lines to disk. Called once per frame.
+    pub fn flush(&mut self) {
+        if let Some(ref mut file) = self.file {
+            file.flush();
+        }
+    }
+
+    /// Shifts `<name>.<n>.<ext>` files up by one, dropping the oldest, and opens a fresh file.
+    fn rotate(&mut self) {
+        self.file = None;
+        if self.max_files > 0 {
+            let _ = DirAccess::remove_absolute(&util::path_with_suffix(&self.path, &self.max_files.to_string()));
+            for i in (1..self.max_files).rev() {
+                let from = util::path_with_suffix(&self.path, &i.to_string());
+                if FileAccess::file_exists(&from) {
+                    let to = util::path_with_suffix(&self.path, &(i + 1).to_string());
+                    let _ = DirAccess::rename_absolute(&from, &to);
+                }
+            }
+            if FileAccess::file_exists(&self.path) {
+                let _ = DirAccess::rename_absolute(&self.path, &util::path_with_suffix(&self.path, "1"));
+            }
+        }
+        self.file = FileAccess::open(&self.path, ModeFlags::WRITE);
+    }
+}
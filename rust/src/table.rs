@@ -0,0 +1,169 @@
+/// Table: Lays out rows of BBCode cells in aligned columns for the console output.
+/// Column widths are measured on the displayed text, so cells can contain tags and links.
+/// The output font is monospaced, so padding with spaces is enough to line columns up.
+use godot::prelude::*;
+
+use crate::ascii_art;
+use crate::util;
+
+/// Horizontal alignment of a column.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Align {
+    Left,
+    Right,
+    Center,
+}
+
+impl Align {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "left" => Some(Align::Left),
+            "right" => Some(Align::Right),
+            "center" => Some(Align::Center),
+            _ => None,
+        }
+    }
+}
+
+struct Column {
+    align: Option<Align>,
+    color: Option<Color>,
+    /// True while every non-empty cell is a number; such columns are right-aligned by default.
+    numeric: bool,
+}
+
+impl Default for Column {
+    fn default() -> Self {
+        Self { align: None, color: None, numeric: true }
+    }
+}
+
+pub struct Table {
+    headers: Vec<String>,
+    rows: Vec<Vec<String>>,
+    columns: Vec<Column>,
+    borders: bool,
+    border_color: Option<Color>,
+}
+
+impl Table {
+    /// Creates a table with the given header cells. With no headers, no header row is drawn.
+    pub fn new(headers: Vec<String>) -> Self {
+        let mut columns = Vec::new();
+        columns.resize_with(headers.len(), Column::default);
+        Self { headers, rows: Vec::new(), columns, borders: false, border_color: None }
+    }
+
+    pub fn add_row(&mut self, cells: Vec<String>) {
+        if cells.len() > self.columns.len() {
+            self.columns.resize_with(cells.len(), Column::default);
+        }
+        for (cell, column) in cells.iter().zip(self.columns.iter_mut()) {
+            let plain = util::bbcode_strip(cell);
+            if !plain.is_empty() && plain.trim().parse::<f64>().is_err() {
+                column.numeric = false;
+            }
+        }
+        self.rows.push(cells);
+    }
+
+    /// Frames the table with box-drawing borders. Borders are drawn in `color`, if given.
+    pub fn set_borders(&mut self, borders: bool, color: Option<Color>) {
+        self.borders = borders;
+        self.border_color = color;
+    }
+
+    pub fn set_column_align(&mut self, column: usize, align: Align) {
+        self.column_mut(column).align = Some(align);
+    }
+
+    pub fn set_column_color(&mut self, column: usize, color: Color) {
+        self.column_mut(column).color = Some(color);
+    }
+
+    fn column_mut(&mut self, column: usize) -> &mut Column {
+        if column >= self.columns.len() {
+            self.columns.resize_with(column + 1, Column::default);
+        }
+        &mut self.columns[column]
+    }
+
+    /// Renders the table into BBCode lines, ready to be printed one by one.
+    pub fn render(&self) -> Vec<String> {
+        let mut widths = vec![0; self.columns.len()];
+        for cells in std::iter::once(&self.headers).chain(&self.rows) {
+            for (width, cell) in widths.iter_mut().zip(cells) {
+                *width = (*width).max(util::bbcode_width(cell));
+            }
+        }
+
+        let box_chars = &ascii_art::BOX_LIGHT;
+        let mut lines = Vec::with_capacity(self.rows.len() + 4);
+        if self.borders {
+            lines.push(self.paint_border(ascii_art::box_rule(&widths, box_chars.top, box_chars.horizontal)));
+        }
+        if !self.headers.is_empty() {
+            lines.push(self.render_row(&self.headers, &widths, true));
+            if self.borders {
+                lines.push(self.paint_border(ascii_art::box_rule(&widths, box_chars.middle, box_chars.horizontal)));
+            }
+        }
+        for cells in &self.rows {
+            lines.push(self.render_row(cells, &widths, false));
+        }
+        if self.borders {
+            lines.push(self.paint_border(ascii_art::box_rule(&widths, box_chars.bottom, box_chars.horizontal)));
+        }
+        lines
+    }
+
+    fn render_row(&self, cells: &[String], widths: &[usize], header: bool) -> String {
+        let separator = if self.borders {
+            self.paint_border(format!(" {} ", ascii_art::BOX_LIGHT.vertical))
+        } else {
+            "  ".to_string()
+        };
+        let mut line = String::new();
+        if self.borders {
+            line.push_str(&self.paint_border(format!("{} ", ascii_art::BOX_LIGHT.vertical)));
+        }
+        for (i, (column, &width)) in self.columns.iter().zip(widths).enumerate() {
+            if i > 0 {
+                line.push_str(&separator);
+            }
+            let cell = cells.get(i).map(String::as_str).unwrap_or("");
+            let padding = width - util::bbcode_width(cell);
+            let align = match column.align {
+                Some(align) => align,
+                None if column.numeric && !header => Align::Right,
+                None => Align::Left,
+            };
+            let (left, right) = match align {
+                Align::Left => (0, padding),
+                Align::Right => (padding, 0),
+                Align::Center => (padding / 2, padding - padding / 2),
+            };
+            // Trailing padding of the last column is dropped, unless a border follows.
+            let right = if i + 1 == self.columns.len() && !self.borders { 0 } else { right };
+            let text = match (header, column.color) {
+                (true, _) => format!("[b]{}[/b]", cell),
+                (false, Some(color)) if !cell.is_empty() => format!("[color={}]{}[/color]", color.to_html(), cell),
+                _ => cell.to_string(),
+            };
+            line.push_str(&" ".repeat(left));
+            line.push_str(&text);
+            line.push_str(&" ".repeat(right));
+        }
+        if self.borders {
+            line.push_str(&self.paint_border(format!(" {}", ascii_art::BOX_LIGHT.vertical)));
+        }
+        line
+    }
+
+    fn paint_border(&self, text: String) -> String {
+        match self.border_color {
+            Some(color) => format!("[color={}]{}[/color]", color.to_html(), text),
+            None => text,
+        }
+    }
+}
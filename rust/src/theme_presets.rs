@@ -0,0 +1,191 @@
+/// Theme presets: Built-in color schemes for the console, selectable at runtime with `theme`.
+/// Each preset is the default theme with its colors replaced, so fonts, margins and shapes stay
+/// the same across presets. Only the colors of the default theme's styles are touched.
+use godot::classes::{StyleBoxFlat, StyleBoxTexture, Theme};
+use godot::prelude::*;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum ThemePreset {
+    Dark,
+    Light,
+    HighContrast,
+    Solarized,
+}
+
+/// Colors a preset is made of. Each ConsoleColors entry takes one of them.
+struct Palette {
+    background: Color,
+    /// Widget text and the tint of scroll bars and borders.
+    foreground: Color,
+    text: Color,
+    /// Hints, debug lines and other secondary text.
+    dim: Color,
+    /// Command mentions, subcommands and numbers.
+    accent: Color,
+    /// Commands and strings.
+    success: Color,
+    error: Color,
+    /// Warnings and keywords.
+    warning: Color,
+    /// Type names.
+    special: Color,
+    selection: Color,
+    find_match: Color,
+    find_current: Color,
+}
+
+impl ThemePreset {
+    pub const ALL: [ThemePreset; 4] = [ThemePreset::Dark, ThemePreset::Light, ThemePreset::HighContrast, ThemePreset::Solarized];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ThemePreset::Dark => "dark",
+            ThemePreset::Light => "light",
+            ThemePreset::HighContrast => "high_contrast",
+            ThemePreset::Solarized => "solarized",
+        }
+    }
+
+    /// Parses a preset name; `high-contrast` is accepted as well.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().replace('-', "_").as_str() {
+            "dark" => Some(ThemePreset::Dark),
+            "light" => Some(ThemePreset::Light),
+            "high_contrast" => Some(ThemePreset::HighContrast),
+            "solarized" => Some(ThemePreset::Solarized),
+            _ => None,
+        }
+    }
+
+    fn palette(self) -> Palette {
+        let rgba = Color::from_rgba;
+        match self {
+            ThemePreset::Dark => Palette {
+                background: rgba(0.11, 0.11, 0.11, 1.0),
+                foreground: rgba(1.0, 1.0, 1.0, 1.0),
+                text: rgba(1.0, 1.0, 1.0, 0.7),
+                dim: rgba(1.0, 1.0, 1.0, 0.35),
+                accent: rgba(0.337, 0.62, 1.0, 1.0),
+                success: rgba(0.6, 0.85, 0.45, 1.0),
+                error: rgba(1.0, 0.3, 0.3, 1.0),
+                warning: rgba(1.0, 0.7, 0.3, 1.0),
+                special: rgba(0.75, 0.55, 1.0, 1.0),
+                selection: rgba(1.0, 1.0, 1.0, 0.12),
+                find_match: rgba(1.0, 0.85, 0.3, 0.25),
+                find_current: rgba(1.0, 0.6, 0.2, 0.6),
+            },
+            ThemePreset::Light => Palette {
+                background: rgba(0.97, 0.97, 0.96, 1.0),
+                foreground: rgba(0.0, 0.0, 0.0, 1.0),
+                text: rgba(0.0, 0.0, 0.0, 0.8),
+                dim: rgba(0.0, 0.0, 0.0, 0.45),
+                accent: rgba(0.1, 0.4, 0.85, 1.0),
+                success: rgba(0.2, 0.55, 0.15, 1.0),
+                error: rgba(0.8, 0.1, 0.1, 1.0),
+                warning: rgba(0.75, 0.45, 0.0, 1.0),
+                special: rgba(0.5, 0.25, 0.75, 1.0),
+                selection: rgba(0.0, 0.0, 0.0, 0.08),
+                find_match: rgba(1.0, 0.8, 0.0, 0.35),
+                find_current: rgba(1.0, 0.5, 0.0, 0.5),
+            },
+            ThemePreset::HighContrast => Palette {
+                background: rgba(0.0, 0.0, 0.0, 1.0),
+                foreground: rgba(1.0, 1.0, 1.0, 1.0),
+                text: rgba(1.0, 1.0, 1.0, 1.0),
+                dim: rgba(0.8, 0.8, 0.8, 1.0),
+                accent: rgba(0.3, 0.8, 1.0, 1.0),
+                success: rgba(0.3, 1.0, 0.3, 1.0),
+                error: rgba(1.0, 0.25, 0.25, 1.0),
+                warning: rgba(1.0, 0.85, 0.0, 1.0),
+                special: rgba(1.0, 0.5, 1.0, 1.0),
+                selection: rgba(1.0, 1.0, 1.0, 0.25),
+                find_match: rgba(1.0, 1.0, 0.0, 0.4),
+                find_current: rgba(1.0, 0.5, 0.0, 0.7),
+            },
+            // Solarized dark: base03 background, base0 text, base01 secondary text
+            ThemePreset::Solarized => Palette {
+                background: rgba(0.0, 0.169, 0.212, 1.0),
+                foreground: rgba(0.576, 0.631, 0.631, 1.0),
+                text: rgba(0.514, 0.580, 0.588, 1.0),
+                dim: rgba(0.345, 0.431, 0.459, 1.0),
+                accent: rgba(0.149, 0.545, 0.824, 1.0),
+                success: rgba(0.522, 0.6, 0.0, 1.0),
+                error: rgba(0.863, 0.196, 0.184, 1.0),
+                warning: rgba(0.710, 0.537, 0.0, 1.0),
+                special: rgba(0.424, 0.443, 0.769, 1.0),
+                selection: rgba(0.576, 0.631, 0.631, 0.15),
+                find_match: rgba(0.710, 0.537, 0.0, 0.3),
+                find_current: rgba(0.796, 0.294, 0.086, 0.5),
+            },
+        }
+    }
+
+    /// Returns a copy of `base` (the default theme) recolored with this preset.
+    pub fn build(self, base: &Gd<Theme>) -> Option<Gd<Theme>> {
+        let mut theme = base.duplicate_ex().deep(true).done()?.try_cast::<Theme>().ok()?;
+        let p = self.palette();
+
+        let console_colors = [
+            ("entry_command_found_color", p.success),
+            ("entry_command_not_found_color", p.error),
+            ("entry_hint_color", p.dim),
+            ("entry_subcommand_color", p.accent),
+            ("entry_text_color", p.text),
+            ("history_highlight_color", p.selection),
+            ("history_match_color", p.accent),
+            ("history_meta_color", p.dim),
+            ("output_command_color", p.success),
+            ("output_command_mention_color", p.accent),
+            ("output_debug_color", p.dim),
+            ("output_error_color", p.error),
+            ("output_find_current_color", p.find_current),
+            ("output_find_match_color", p.find_match),
+            ("output_text_color", p.text),
+            ("output_value_keyword_color", p.warning),
+            ("output_value_number_color", p.accent),
+            ("output_value_string_color", p.success),
+            ("output_value_type_color", p.special),
+            ("output_warning_color", p.warning),
+        ];
+        for (name, color) in console_colors {
+            theme.set_color(name, "ConsoleColors", color);
+        }
+
+        for (name, theme_type, color) in [
+            ("font_color", "Label", p.text),
+            ("font_color", "Button", p.dim),
+            ("font_hover_color", "Button", p.text),
+            ("font_color", "LineEdit", p.text),
+            ("font_selected_color", "TabBar", p.foreground),
+            ("font_unselected_color", "TabBar", p.dim),
+            ("font_hovered_color", "TabBar", p.text),
+        ] {
+            theme.set_color(name, theme_type, color);
+        }
+
+        for theme_type in ["Panel", "PanelContainer"] {
+            if let Some(mut style) = stylebox::<StyleBoxFlat>(&theme, "panel", theme_type) {
+                style.set_bg_color(p.background);
+            }
+        }
+        for name in ["normal", "focus"] {
+            if let Some(mut style) = stylebox::<StyleBoxFlat>(&theme, name, "TextEdit") {
+                style.set_border_color(Color { a: 0.13, ..p.foreground });
+            }
+        }
+        // Scroll bar textures are white; tinting them keeps them visible on light backgrounds
+        for name in ["grabber", "grabber_highlight", "grabber_pressed", "scroll", "scroll_focus"] {
+            if let Some(mut style) = stylebox::<StyleBoxTexture>(&theme, name, "VScrollBar") {
+                style.set_modulate(p.foreground);
+            }
+        }
+        Some(theme)
+    }
+}
+
+fn stylebox<T: GodotClass + Inherits<godot::classes::StyleBox>>(theme: &Gd<Theme>, name: &str, theme_type: &str) -> Option<Gd<T>> {
+    if !theme.has_stylebox(name, theme_type) {
+        return None;
+    }
+    theme.get_stylebox(name, theme_type)?.try_cast::<T>().ok()
+}
@@ -2,28 +2,86 @@
 /// Port of LimboConsole from GDScript to Rust via gdext.
 /// Registered as an engine singleton automatically by gdext.
 /// Creates an internal CanvasLayer and adds it to the scene tree.
-use godot::classes::control::{FocusMode, LayoutPreset, SizeFlags};
+use godot::classes::control::{CursorShape, FocusMode, LayoutPreset, MouseFilter, SizeFlags};
 use godot::classes::{
-    file_access::ModeFlags, CanvasLayer, Control, Engine, FileAccess, IObject, InputEvent, InputEventKey, InputMap, Os, PanelContainer, ProjectSettings, ResourceLoader, RichTextLabel, SceneTree,
-    Theme, VBoxContainer,
+    file_access::ModeFlags, Button, CanvasLayer, Control, DisplayServer, Engine, FileAccess, HBoxContainer, IObject, ImageTexture, InputEvent, InputEventKey, InputEventMouseButton, InputEventMouseMotion, InputMap, Os, PanelContainer, PhysicsServer2D, PhysicsServer3D, ProjectSettings, ResourceLoader, RichTextLabel, SceneTree, TabBar, Texture2D,
+    Theme, Time, VBoxContainer, Window,
 };
-use godot::global::{ease, remap, Key};
+use godot::classes::logger::ErrorType;
+use godot::classes::window::WindowInitialPosition;
+use godot::global::{ease, Key, MouseButton};
 use godot::prelude::*;
 
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
+use std::ops::Range;
 
 use crate::ascii_art;
 use crate::builtin_commands::{self, BuiltinCommands};
 use crate::command_entry::CommandEntry;
 use crate::command_entry_highlighter::CommandEntryHighlighter;
-use crate::command_history::{self, CommandHistory, WrappingIterator};
-use crate::console_options::ConsoleOptions;
+use crate::command_history::{self, CommandHistory, DuplicateMode, HistoryCursor, HistoryFilter};
+use crate::console_options::{ConsoleOptions, Dock, PauseMode};
+use crate::engine_logger::{self, EngineLog, EngineLogger};
+use crate::find_bar::FindBar;
 use crate::history_gui::HistoryGui;
+use crate::live_line::LiveLine;
+use crate::output_buffer::{LineImage, LinePrefix, LogLevel, OutputBuffer, OutputFilter};
+use crate::overlay::Overlay;
+use crate::session_log::SessionLog;
+use crate::table::{Align, Table};
+use crate::theme_presets::ThemePreset;
+use crate::user_prefs::{UserPrefs, DEFAULT_PREFS_FILE};
 use crate::util;
+use crate::variant_printer::{self, PrettyValue, ValueStyle};
 
 const THEME_DEFAULT: &str = "res://addons/tiny_console/res/default_theme.tres";
+/// Theme name that stands for the project's theme (`appearance/custom_theme`).
+const THEME_NAME_DEFAULT: &str = "default";
 const MAX_SUBCOMMANDS: usize = 4;
 const CONSOLE_COLORS_THEME_TYPE: &str = "ConsoleColors";
+/// Exit status recorded in history for unknown commands (as in bash).
+const EXIT_COMMAND_NOT_FOUND: i32 = 127;
+/// Exit status recorded in history when arguments fail to parse.
+const EXIT_BAD_ARGUMENTS: i32 = 2;
+// Prefixes of output `[url]` metas; the rest of the meta is encoded with `util::meta_encode`.
+/// Script location (`script:res://file.gd:42`), opened in the system's editor.
+const META_SCRIPT_PREFIX: &str = "script:";
+/// Command mention; inserts the command (or `help <command>` with Shift) into the entry.
+const META_COMMAND_PREFIX: &str = "cmd:";
+/// Command line executed when clicked.
+const META_RUN_PREFIX: &str = "run:";
+/// Text put into the entry when clicked.
+const META_FILL_PREFIX: &str = "fill:";
+/// Fold toggle of a printed value (`fold:<line id>:<path>`).
+const META_FOLD_PREFIX: &str = "fold:";
+/// White texture that `[img color=..]` tints to draw the color swatches of printed values.
+const SWATCH_TEXTURE: &str = "res://addons/tiny_console/res/swatch.tres";
+/// Channel of lines printed without one: command echoes, command output and plain prints.
+const COMMANDS_CHANNEL: &str = "commands";
+/// Channel of messages captured from the engine.
+const ENGINE_CHANNEL: &str = "engine";
+/// Id of the output context menu item that copies the last command's output.
+const OUTPUT_MENU_COPY_COMMAND_OUTPUT: i32 = 100;
+/// Width of progress bars, in characters.
+const PROGRESS_BAR_WIDTH: usize = 20;
+/// Printed values whose fold toggles keep working; older ones become static.
+const MAX_FOLDABLE_VALUES: usize = 64;
+/// Thickness of the grab area on the panel's inner edge, in pixels.
+const RESIZE_GRIP_SIZE: f32 = 6.0;
+/// Smallest share of the viewport the panel can be dragged down to.
+const MIN_SIZE_RATIO: f32 = 0.1;
+/// Output font sizes reachable with the font size shortcuts.
+const OUTPUT_FONT_SIZES: std::ops::RangeInclusive<i32> = 8..=72;
+
+/// Change to the game made while the console is open.
+enum AppliedPause {
+    /// The scene tree was paused, unless it already was.
+    Tree { was_paused: bool },
+    /// `Engine.time_scale` was changed from `previous`.
+    TimeScale { previous: f64 },
+    /// Opened with `open_console_unpaused`; nothing to undo.
+    Unpaused,
+}
 
 /// In-game console singleton for registering commands, printing output, and executing scripts.
 ///
@@ -38,16 +96,26 @@ pub struct TinyConsole {
 
     // GUI nodes
     control: Option<Gd<PanelContainer>>,
+    // OS window holding the panel in the window dock; created when first needed
+    window: Option<Gd<Window>>,
+    // Grab area on the panel's inner edge for resizing by mouse
+    resize_grip: Option<Gd<Control>>,
     control_block: Option<Gd<Control>>,
     output: Option<Gd<RichTextLabel>>,
+    output_buffer: OutputBuffer,
     entry: Option<Gd<CommandEntry>>,
     history_gui: Option<Gd<HistoryGui>>,
+    find_bar: Option<Gd<FindBar>>,
+    channel_tabs: Option<Gd<TabBar>>,
+    // Recent lines shown while the console is closed, if enabled
+    overlay: Option<Overlay>,
+    level_buttons: Vec<Gd<Button>>,
     previous_gui_focus: Option<Gd<Control>>,
 
     // Theme colors
     output_command_color: Color,
     pub(crate) output_command_mention_color: Color,
-    output_error_color: Color,
+    pub(crate) output_error_color: Color,
     pub(crate) output_warning_color: Color,
     output_text_color: Color,
     pub(crate) output_debug_color: Color,
@@ -56,6 +124,7 @@ pub struct TinyConsole {
     entry_command_found_color: Color,
     entry_subcommand_color: Color,
     entry_command_not_found_color: Color,
+    value_style: ValueStyle,
 
     // State
     enabled: bool,
@@ -65,15 +134,41 @@ pub struct TinyConsole {
     pub(crate) aliases: HashMap<String, Vec<String>>,
     pub(crate) command_descriptions: HashMap<String, String>,
     argument_autocomplete_sources: HashMap<(String, usize), Callable>,
-    history: CommandHistory,
-    history_iter: WrappingIterator,
+    pub(crate) history: CommandHistory,
+    history_iter: HistoryCursor,
+    history_profile: String,
+    history_file: String,
+    pub(crate) min_log_level: LogLevel,
+    // Channel printed lines go to; changed for the duration of `with_channel`
+    output_channel: usize,
+    // Output line ids of the running (or last) command's output, and of the command before it
+    command_output_start: Option<u64>,
+    previous_command_output: Option<Range<u64>>,
+    engine_logger: Option<Gd<EngineLogger>>,
+    session_log: Option<SessionLog>,
+    // Foldable printed values by output line id, oldest first
+    foldable_values: VecDeque<(u64, PrettyValue)>,
     autocomplete_matches: Vec<String>,
     eval_inputs: HashMap<String, Variant>,
     silent: bool,
-    was_already_paused: bool,
+    pause_mode: PauseMode,
+    // What opening the console did to the game, undone when it's hidden
+    applied_pause: Option<AppliedPause>,
     open_t: f32,
+    // Time of the previous process frame, to slide by real elapsed time
+    last_frame_usec: u64,
     open_speed: f32,
     is_open: bool,
+    dock: Dock,
+    // Panel size as a share of the viewport, for the top/bottom and the side docks
+    height_ratio: f32,
+    width_ratio: f32,
+    resizing: bool,
+    // Output font size set with the font size shortcuts; `None` uses the theme's
+    output_font_size: Option<i32>,
+    // Theme chosen with `theme`: a preset name or a resource path; empty for the project's theme
+    theme_name: String,
+    user_prefs: UserPrefs,
     // Pending command from signal callback — executed in on_process_frame
     // to avoid re-entrant borrow issues with #[func] dispatch.
     pending_command: Option<String>,
@@ -123,9 +218,25 @@ impl TinyConsole {
             // Register input actions
             s.register_input_actions();
 
+            if !s.options.session_log_file.is_empty() {
+                let path = s.options.session_log_file.clone();
+                let max_bytes = s.options.session_log_max_size_kb.max(0) as u64 * 1024;
+                s.session_log = SessionLog::start(&path, max_bytes, s.options.session_log_max_files.max(0) as u32);
+                if s.session_log.is_none() {
+                    godot_error!("TinyConsole: Can't open session log: {}", path);
+                }
+            }
+
+            if s.options.capture_engine_output {
+                let logger = EngineLogger::new_gd();
+                Os::singleton().add_logger(&logger);
+                s.engine_logger = Some(logger);
+            }
+
             // Build GUI
             s.build_gui();
             s.init_theme();
+            s.apply_output_font_size();
 
             // Add canvas layer to scene tree
             if let Some(ref mut cl) = s.canvas_layer {
@@ -140,12 +251,7 @@ impl TinyConsole {
             }
 
             // Hide console initially
-            if let Some(ref mut control) = s.control {
-                control.set_visible(false);
-            }
-            if let Some(ref mut block) = s.control_block {
-                block.set_visible(false);
-            }
+            s.set_gui_visible(false);
 
             // Connect signals
             if let Some(ref entry) = s.entry {
@@ -160,12 +266,56 @@ impl TinyConsole {
                 entry_node.connect("scroll_down_requested", &Callable::from_object_method(&gd_ref, "on_scroll_down_requested"));
             }
 
+            if let Some(ref hg) = s.history_gui {
+                let mut hg_node: Gd<godot::classes::Node> = hg.clone().upcast();
+                hg_node.connect("scroll_requested", &Callable::from_object_method(&gd_ref, "on_history_scroll_requested"));
+                hg_node.connect("delete_requested", &Callable::from_object_method(&gd_ref, "on_history_delete_requested"));
+                hg_node.connect("layout_changed", &Callable::from_object_method(&gd_ref, "on_history_layout_changed"));
+            }
+
+            if let Some(ref fb) = s.find_bar {
+                let mut fb_node: Gd<godot::classes::Node> = fb.clone().upcast();
+                fb_node.connect("query_changed", &Callable::from_object_method(&gd_ref, "on_find_query_changed"));
+                fb_node.connect("match_step_requested", &Callable::from_object_method(&gd_ref, "on_find_match_step_requested"));
+                fb_node.connect("filter_toggled", &Callable::from_object_method(&gd_ref, "on_find_filter_toggled"));
+                fb_node.connect("closed", &Callable::from_object_method(&gd_ref, "on_find_closed"));
+            }
+
+            if let Some(ref tabs) = s.channel_tabs {
+                let mut tabs_node: Gd<godot::classes::Node> = tabs.clone().upcast();
+                tabs_node.connect("tab_changed", &Callable::from_object_method(&gd_ref, "on_channel_tab_changed"));
+            }
+            s.update_channel_tabs();
+
+            if let Some(ref grip) = s.resize_grip {
+                let mut grip_node: Gd<godot::classes::Node> = grip.clone().upcast();
+                grip_node.connect("gui_input", &Callable::from_object_method(&gd_ref, "on_resize_grip_input"));
+            }
+
+            if let Some(ref output) = s.output {
+                let mut output_node: Gd<godot::classes::Node> = output.clone().upcast();
+                output_node.connect("meta_clicked", &Callable::from_object_method(&gd_ref, "on_output_meta_clicked"));
+                if let Some(menu) = output.get_menu() {
+                    let mut menu_node: Gd<godot::classes::Node> = menu.upcast();
+                    menu_node.connect("id_pressed", &Callable::from_object_method(&gd_ref, "on_output_menu_id_pressed"));
+                }
+            }
+
+            for (i, button) in s.level_buttons.iter().enumerate() {
+                let mut button_node: Gd<godot::classes::Node> = button.clone().upcast();
+                button_node.connect("toggled", &Callable::from_object_method(&gd_ref, "on_level_toggled").bind(&[(i as i64).to_variant()]));
+            }
+            s.update_visible_levels();
+
             // Connect canvas_layer process and input
             if s.canvas_layer.is_some() {
                 let mut tree = Self::get_scene_tree();
                 tree.connect("process_frame", &Callable::from_object_method(&gd_ref, "on_process_frame"));
             }
 
+            let mut project_settings = ProjectSettings::singleton();
+            project_settings.connect("settings_changed", &Callable::from_object_method(&gd_ref, "on_project_settings_changed"));
+
             // Register builtin commands
             let builtin = BuiltinCommands::new_gd();
             builtin_commands::register(&mut s, &builtin);
@@ -203,6 +353,86 @@ impl TinyConsole {
         }
     }
 
+    // --- Settings reload ---
+
+    /// Reads the console settings from Project Settings again and applies the ones that changed:
+    /// size, dock, opacity, open speed, theme, aliases, history and scrollback limits. The dock,
+    /// pause mode, line prefix and log level can also be changed at runtime, so they are only
+    /// applied if their setting changed. Called automatically when Project Settings change.
+    #[func]
+    pub fn reload_options(&mut self) {
+        if !self.initialized {
+            return;
+        }
+        let mut options = ConsoleOptions::new();
+        options.load_from_project_settings();
+        // Games may change unrelated settings at runtime
+        if options == self.options {
+            return;
+        }
+        let previous = std::mem::replace(&mut self.options, options);
+
+        if self.options.aliases != previous.aliases {
+            // Aliases from the previous settings are replaced, unless they were redefined since
+            for (alias, target) in &previous.aliases {
+                if self.aliases.get(alias) == Some(&self.parse_command_line(target)) {
+                    self.aliases.remove(alias);
+                }
+            }
+            self.add_aliases_from_config();
+        }
+
+        if self.options.pause_mode != previous.pause_mode {
+            self.set_pause_mode(GString::from(self.options.pause_mode.as_str()));
+        }
+        if self.options.line_prefix != previous.line_prefix {
+            self.set_line_prefix(GString::from(self.options.line_prefix.as_str()));
+        }
+        if self.options.min_log_level != previous.min_log_level {
+            self.set_min_log_level(GString::from(self.options.min_log_level.as_str()));
+        }
+
+        if self.options.history_ignore_space != previous.history_ignore_space
+            || self.options.history_duplicates != previous.history_duplicates
+            || self.options.history_ignore_failed != previous.history_ignore_failed
+            || self.options.history_ignore_patterns != previous.history_ignore_patterns
+        {
+            self.history.set_filter(HistoryFilter::new(
+                self.options.history_ignore_space,
+                DuplicateMode::from_setting(&self.options.history_duplicates),
+                self.options.history_ignore_failed,
+                &self.options.history_ignore_patterns,
+            ));
+        }
+        if self.options.scrollback_lines != previous.scrollback_lines {
+            self.output_buffer.set_max_lines(self.options.scrollback_lines.max(1) as usize);
+        }
+
+        self.open_speed = self.options.open_speed;
+        if self.options.opacity != previous.opacity {
+            if let Some(ref mut panel) = self.control {
+                panel.set_modulate(Color::from_rgba(1.0, 1.0, 1.0, self.options.opacity));
+            }
+        }
+        let dock = Dock::from_name(&self.options.dock).unwrap_or(Dock::Top);
+        let ratios_changed = self.options.height_ratio != previous.height_ratio || self.options.width_ratio != previous.width_ratio;
+        if ratios_changed {
+            // Sizes dragged by the player still take precedence
+            self.height_ratio = self.user_prefs.get_float("layout", "height_ratio").unwrap_or(self.options.height_ratio);
+            self.width_ratio = self.user_prefs.get_float("layout", "width_ratio").unwrap_or(self.options.width_ratio);
+        }
+        if self.options.dock != previous.dock && dock != self.dock {
+            self.set_dock(GString::from(dock.name()));
+        } else if ratios_changed {
+            self.apply_dock();
+        }
+
+        if self.options.custom_theme != previous.custom_theme {
+            self.init_theme();
+            self.apply_output_font_size();
+        }
+    }
+
     // --- Console visibility ---
 
     /// Opens the console.
@@ -217,6 +447,15 @@ impl TinyConsole {
         }
     }
 
+    /// Opens the console while the game keeps running, whatever the pause mode.
+    #[func]
+    pub fn open_console_unpaused(&mut self) {
+        if self.enabled && !self.is_open {
+            self.applied_pause = Some(AppliedPause::Unpaused);
+            self.open_console();
+        }
+    }
+
     /// Closes the console.
     #[func]
     pub fn close_console(&mut self) {
@@ -229,7 +468,7 @@ impl TinyConsole {
                 hg.set_visible(false);
             }
             if self.options.persist_history {
-                self.history.save(command_history::HISTORY_FILE);
+                self.history.save(&self.history_file);
             }
         }
     }
@@ -253,19 +492,14 @@ impl TinyConsole {
     /// Toggles the history search panel. When opened, filters history entries by the current input text.
     #[func]
     pub fn toggle_history(&mut self) {
-        let was_visible = self.history_gui.as_ref().map_or(false, |hg| hg.is_visible());
+        let was_visible = self.history_gui.as_ref().is_some_and(|hg| hg.is_visible());
 
         if let Some(ref mut hg) = self.history_gui {
             hg.set_visible(!was_visible);
         }
 
         if !was_visible {
-            let entry_text = self.get_entry_text();
-            let results = self.history.fuzzy_match(&entry_text);
-            let packed: PackedStringArray = results.iter().map(|s| GString::from(s.as_str())).collect();
-            if let Some(ref mut hg) = self.history_gui {
-                hg.bind_mut().set_search_results(packed);
-            }
+            self.update_history_search();
         }
     }
 
@@ -275,25 +509,63 @@ impl TinyConsole {
         if let Some(ref mut output) = self.output {
             output.set_text("");
         }
+        self.output_buffer.clear();
     }
 
     /// Clears command history from memory and deletes the history file.
     #[func]
     pub fn erase_history(&mut self) {
         self.history.clear();
-        let path: GString = command_history::HISTORY_FILE.into();
+        let path: GString = self.history_file.as_str().into();
         if let Some(mut file) = FileAccess::open(&path, ModeFlags::WRITE) {
             file.store_string("");
         }
     }
 
+    /// Switches to a named history profile, saving the current history first.
+    /// Each profile is stored in its own file next to `history/history_file` (e.g. `tiny_console_history.server.log`).
+    /// An empty name selects the default history file. The profile can also be set at launch with
+    /// `--tiny-console-history-profile=<name>`.
+    #[func]
+    pub fn set_history_profile(&mut self, profile: GString) {
+        let profile_str = profile.to_string();
+        if !command_history::is_valid_profile_name(&profile_str) {
+            godot_error!("TinyConsole: Invalid history profile name: {}. Use letters, digits, '_' and '-'.", profile_str);
+            return;
+        }
+        if profile_str == self.history_profile {
+            return;
+        }
+        if self.options.persist_history {
+            self.history.trim(self.options.history_lines as usize);
+            self.history.save(&self.history_file);
+        }
+        self.history_profile = profile_str;
+        self.history_file = command_history::profile_path(&self.options.history_file, &self.history_profile);
+        if self.options.persist_history {
+            self.history.reload(&self.history_file);
+        }
+    }
+
+    /// Returns the name of the active history profile, or an empty string for the default one.
+    #[func]
+    pub fn get_history_profile(&self) -> GString {
+        GString::from(self.history_profile.as_str())
+    }
+
+    /// Returns the path of the history file used by the active profile.
+    #[func]
+    pub fn get_history_file(&self) -> GString {
+        GString::from(self.history_file.as_str())
+    }
+
     // --- Output methods ---
 
     /// Prints an informational message to the console.
     #[func]
     pub fn info(&mut self, line: GString) {
-        let stdout = self.options.print_to_stdout;
-        self.print_line_internal(&line.to_string(), stdout);
+        let stdout = self.mirrors_to_stdout(LogLevel::Info);
+        self.print_line_level(&line.to_string(), LogLevel::Info, stdout);
     }
 
     /// Prints an error message prefixed with `ERROR:` using the error color.
@@ -301,8 +573,8 @@ impl TinyConsole {
     pub fn error(&mut self, line: GString) {
         let color = self.output_error_color.to_html();
         let msg = format!("[color={}]ERROR:[/color] {}", color, line);
-        let stdout = self.options.print_to_stdout;
-        self.print_line_internal(&msg, stdout);
+        let stdout = self.mirrors_to_stdout(LogLevel::Error);
+        self.print_line_level(&msg, LogLevel::Error, stdout);
     }
 
     /// Prints a warning message prefixed with `WARNING:` using the warning color.
@@ -310,8 +582,8 @@ impl TinyConsole {
     pub fn warn(&mut self, line: GString) {
         let color = self.output_warning_color.to_html();
         let msg = format!("[color={}]WARNING:[/color] {}", color, line);
-        let stdout = self.options.print_to_stdout;
-        self.print_line_internal(&msg, stdout);
+        let stdout = self.mirrors_to_stdout(LogLevel::Warning);
+        self.print_line_level(&msg, LogLevel::Warning, stdout);
     }
 
     /// Prints a debug message prefixed with `DEBUG:` using the debug color.
@@ -319,15 +591,69 @@ impl TinyConsole {
     pub fn debug_msg(&mut self, line: GString) {
         let color = self.output_debug_color.to_html();
         let msg = format!("[color={}]DEBUG: {}[/color]", color, line);
-        let stdout = self.options.print_to_stdout;
-        self.print_line_internal(&msg, stdout);
+        let stdout = self.mirrors_to_stdout(LogLevel::Debug);
+        self.print_line_level(&msg, LogLevel::Debug, stdout);
+    }
+
+    /// Prints an informational message to a channel, which gets its own tab above the output.
+    #[func]
+    pub fn info_to(&mut self, channel: GString, line: GString) {
+        self.with_channel(&channel.to_string(), |s| s.info(line));
+    }
+
+    /// Prints an error message to a channel.
+    #[func]
+    pub fn error_to(&mut self, channel: GString, line: GString) {
+        self.with_channel(&channel.to_string(), |s| s.error(line));
+    }
+
+    /// Prints a warning message to a channel.
+    #[func]
+    pub fn warn_to(&mut self, channel: GString, line: GString) {
+        self.with_channel(&channel.to_string(), |s| s.warn(line));
+    }
+
+    /// Prints a debug message to a channel.
+    #[func]
+    pub fn debug_msg_to(&mut self, channel: GString, line: GString) {
+        self.with_channel(&channel.to_string(), |s| s.debug_msg(line));
+    }
+
+    /// Prints a raw line to a channel.
+    #[func]
+    pub fn print_line_to(&mut self, channel: GString, line: GString) {
+        self.with_channel(&channel.to_string(), |s| s.print_line(line));
+    }
+
+    /// Shows the tab of a channel in the output; an empty name or `all` shows every channel.
+    /// Returns false if nothing was printed to the channel yet.
+    #[func]
+    pub fn select_channel(&mut self, channel: GString) -> bool {
+        let channel = channel.to_string();
+        let view = if channel.is_empty() || channel.eq_ignore_ascii_case("all") {
+            None
+        } else {
+            match self.output_buffer.find_channel(&channel) {
+                Some(i) => Some(i),
+                None => return false,
+            }
+        };
+        self.output_buffer.set_view(view);
+        self.update_channel_tabs();
+        true
+    }
+
+    /// Returns the names of all channels, in the order of their tabs.
+    #[func]
+    pub fn get_channels(&self) -> PackedStringArray {
+        self.output_buffer.channels().iter().map(|c| GString::from(c.name.as_str())).collect()
     }
 
     /// Prints text surrounded by an ASCII art box.
     #[func]
     pub fn print_boxed(&mut self, line: GString) {
         let lines = ascii_art::str_to_boxed_art(&line.to_string());
-        let stdout = self.options.print_to_stdout;
+        let stdout = self.mirrors_to_stdout(LogLevel::Info);
         for l in lines {
             self.print_line_internal(&l, stdout);
         }
@@ -336,16 +662,255 @@ impl TinyConsole {
     /// Prints a raw line to the console. Supports BBCode tags for rich text formatting.
     #[func]
     pub fn print_line(&mut self, line: GString) {
-        let stdout = self.options.print_to_stdout;
+        let stdout = self.mirrors_to_stdout(LogLevel::Info);
         self.print_line_internal(&line.to_string(), stdout);
     }
 
+    /// Prints a clickable link that executes `command_line` as if it was typed into the console.
+    #[func]
+    pub fn print_action(&mut self, label: GString, command_line: GString) {
+        let color = self.output_command_mention_color.to_html();
+        let msg = format!("[url={}{}][color={}][u]{}[/u][/color][/url]", META_RUN_PREFIX, util::meta_encode(&command_line.to_string()), color, label);
+        let stdout = self.mirrors_to_stdout(LogLevel::Info);
+        self.print_line_internal(&msg, stdout);
+    }
+
+    /// Prints a line that can be changed later through the returned handle:
+    /// `update(text)` rewrites it in place and `finish()` ends the updates.
+    #[func]
+    pub fn print_live(&mut self, text: GString) -> Gd<LiveLine> {
+        self.print_live_internal(text.to_string(), None)
+    }
+
+    /// Prints a live line with a progress bar filled to `value` (0 to 1).
+    /// Move the bar with `set_progress(value)` on the returned handle.
+    #[func]
+    pub fn print_progress(&mut self, label: GString, value: f64) -> Gd<LiveLine> {
+        let value = value.clamp(0.0, 1.0);
+        let text = self.format_progress(&label.to_string(), value);
+        self.print_live_internal(text, Some((label.to_string(), value)))
+    }
+
+    /// Prints a texture. `size` is in pixels; set one axis to 0 to keep the aspect ratio,
+    /// or both to show the texture at its own size. Textures that weren't loaded from a file,
+    /// such as viewport textures, are printed as a snapshot of their current contents.
+    #[func]
+    pub fn print_image(&mut self, texture: Gd<Texture2D>, size: Vector2i) {
+        let Some(texture) = Self::printable_texture(texture, size) else {
+            self.error("Can't print image: the texture has no image data.".into());
+            return;
+        };
+        if self.silent {
+            return;
+        }
+        let image = LineImage { texture, size };
+        let id = self.output_buffer.next_id();
+        self.output_buffer.push_image(image.clone(), LogLevel::Info, self.output_channel);
+        let console_visible = self.control.as_ref().is_some_and(|c| c.is_visible());
+        if let Some(ref mut overlay) = self.overlay {
+            if !console_visible && LogLevel::Info >= self.min_log_level && overlay.accepts(LogLevel::Info) {
+                overlay.push_image(id, image);
+            }
+        }
+    }
+
+    /// Pretty-prints a value: Arrays, Dictionaries and Objects (with their exported properties)
+    /// are shown as indented, foldable sections.
+    #[func]
+    pub fn print_variant(&mut self, value: Variant) {
+        let stdout = self.mirrors_to_stdout(LogLevel::Info);
+        self.print_variant_internal(value, stdout);
+    }
+
+    /// Prints rows of cells in aligned columns under the given headers (pass none to omit the header row).
+    /// Each row is an Array of values; cells may contain BBCode. Numeric columns are right-aligned.
+    #[func]
+    pub fn print_table(&mut self, headers: PackedStringArray, rows: VarArray) {
+        self.print_table_ex(headers, rows, VarDictionary::new());
+    }
+
+    /// Like `print_table`, with layout options: `borders` (bool), `align` (Array of "left", "right"
+    /// or "center") and `colors` (Array of Color, or null to keep a column's default color).
+    #[func]
+    pub fn print_table_ex(&mut self, headers: PackedStringArray, rows: VarArray, options: VarDictionary) {
+        let mut table = Table::new(headers.as_slice().iter().map(GString::to_string).collect());
+        for row in rows.iter_shared() {
+            let cells: Vec<String> = match row.try_to::<VarArray>() {
+                Ok(cells) => cells.iter_shared().map(|cell| cell.stringify().to_string()).collect(),
+                Err(_) => vec![row.stringify().to_string()],
+            };
+            table.add_row(cells);
+        }
+        if options.get("borders").is_some_and(|v| v.booleanize()) {
+            table.set_borders(true, Some(self.output_debug_color));
+        }
+        if let Some(align) = options.get("align").and_then(|v| v.try_to::<VarArray>().ok()) {
+            for (i, name) in align.iter_shared().enumerate() {
+                match Align::from_name(&name.stringify().to_string()) {
+                    Some(align) => table.set_column_align(i, align),
+                    None if name.is_nil() => {}
+                    None => godot_warn!("TinyConsole: Unknown table alignment: {}", name),
+                }
+            }
+        }
+        if let Some(colors) = options.get("colors").and_then(|v| v.try_to::<VarArray>().ok()) {
+            for (i, color) in colors.iter_shared().enumerate() {
+                if let Ok(color) = color.try_to::<Color>() {
+                    table.set_column_color(i, color);
+                }
+            }
+        }
+        let stdout = self.mirrors_to_stdout(LogLevel::Info);
+        self.print_table_internal(&table, stdout);
+    }
+
     /// Prints a raw line to the console with explicit control over stdout mirroring.
     #[func]
     pub fn print_line_ex(&mut self, line: GString, stdout: bool) {
         self.print_line_internal(&line.to_string(), stdout);
     }
 
+    /// Hides output lines below `level` (`debug`, `info`, `warning` or `error`).
+    /// Hidden lines are kept and shown again when the level is lowered.
+    /// Returns `false` if the level name is unknown.
+    #[func]
+    pub fn set_min_log_level(&mut self, level: GString) -> bool {
+        match LogLevel::from_name(&level.to_string()) {
+            Some(level) => {
+                self.min_log_level = level;
+                self.update_visible_levels();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Returns the name of the minimum level shown in the output.
+    #[func]
+    pub fn get_min_log_level(&self) -> GString {
+        GString::from(self.min_log_level.name())
+    }
+
+    /// Sets the timing prefix shown before each output line: `none`, `wall_clock`, `uptime`,
+    /// `process_frame` or `physics_frame`. Lines already printed are shown with the new prefix.
+    /// Returns `false` if the prefix name is unknown.
+    #[func]
+    pub fn set_line_prefix(&mut self, prefix: GString) -> bool {
+        match LinePrefix::from_name(&prefix.to_string()) {
+            Some(prefix) => {
+                self.output_buffer.set_prefix(prefix);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Writes all lines kept in the output to a file, as plain text or with BBCode tags.
+    /// Relative paths are saved under `user://`. Returns `false` if the file can't be written.
+    #[func]
+    pub fn dump_output(&mut self, path: GString, bbcode: bool) -> bool {
+        let path = util::resolve_user_path(&path.to_string());
+        match FileAccess::open(path.as_str(), ModeFlags::WRITE) {
+            Some(mut file) => file.store_string(self.output_buffer.export(bbcode).as_str()),
+            None => false,
+        }
+    }
+
+    /// Returns the path of the session log, or an empty string if session logging is off.
+    #[func]
+    pub fn get_session_log_file(&self) -> GString {
+        GString::from(self.session_log.as_ref().map_or("", |log| log.path()))
+    }
+
+    /// Returns the name of the timing prefix shown before each output line.
+    #[func]
+    pub fn get_line_prefix(&self) -> GString {
+        GString::from(self.output_buffer.prefix().name())
+    }
+
+    /// Moves the console to a dock: `top`, `bottom`, `left`, `right` or `window` (a separate
+    /// OS window). Returns `false` if the dock name is unknown.
+    #[func]
+    pub fn set_dock(&mut self, dock: GString) -> bool {
+        let Some(dock) = Dock::from_name(&dock.to_string()) else {
+            return false;
+        };
+        self.dock = dock;
+        self.apply_dock();
+        let visible = self.control.as_ref().is_some_and(|c| c.is_visible());
+        self.set_gui_visible(visible);
+        if visible {
+            if let Some(ref mut entry) = self.entry {
+                entry.grab_focus();
+            }
+        }
+        true
+    }
+
+    /// Sets what happens to the game while the console is open: `none`, `pause`,
+    /// `pause_keep_physics_servers` or `slow_motion`. Applies right away if the console is open.
+    /// Returns `false` if the mode name is unknown.
+    #[func]
+    pub fn set_pause_mode(&mut self, mode: GString) -> bool {
+        let Some(mode) = PauseMode::from_name(&mode.to_string()) else {
+            return false;
+        };
+        self.pause_mode = mode;
+        if self.control.as_ref().is_some_and(|c| c.is_visible()) {
+            self.release_pause();
+            self.apply_pause();
+        }
+        true
+    }
+
+    /// Returns the name of the pause mode.
+    #[func]
+    pub fn get_pause_mode(&self) -> GString {
+        GString::from(self.pause_mode.name())
+    }
+
+    /// Switches the console's theme to a preset (`dark`, `light`, `high_contrast` or `solarized`),
+    /// or to the Theme resource at a path. `default` goes back to the project's theme. The choice
+    /// is remembered for later sessions. Lines already printed keep their colors.
+    /// Returns `false` if there is no such preset or theme.
+    #[func]
+    pub fn set_theme(&mut self, theme: GString) -> bool {
+        let name = theme.to_string();
+        let name = match ThemePreset::from_name(&name) {
+            Some(preset) => preset.name().to_string(),
+            None if name.is_empty() || name == THEME_NAME_DEFAULT => String::new(),
+            None if load_theme_file(&name).is_some() => name,
+            None => return false,
+        };
+        let pref = if name.is_empty() { Variant::nil() } else { name.to_variant() };
+        self.user_prefs.set("appearance", "theme", pref);
+        self.theme_name = name;
+        self.init_theme();
+        self.apply_output_font_size();
+        true
+    }
+
+    /// Returns the name or path of the console's theme, or `default` for the project's theme.
+    #[func]
+    pub fn get_theme(&self) -> GString {
+        match self.theme_name.as_str() {
+            "" => GString::from(THEME_NAME_DEFAULT),
+            name => GString::from(name),
+        }
+    }
+
+    /// Returns the names accepted by `set_theme()` besides resource paths.
+    #[func]
+    pub fn get_theme_presets(&self) -> PackedStringArray {
+        std::iter::once(THEME_NAME_DEFAULT).chain(ThemePreset::ALL.iter().map(|p| p.name())).map(GString::from).collect()
+    }
+
+    /// Returns the name of the console's dock.
+    #[func]
+    pub fn get_dock(&self) -> GString {
+        GString::from(self.dock.name())
+    }
+
     // --- Command registration ---
 
     /// Registers a command that can be invoked from the console.
@@ -479,7 +1044,7 @@ impl TinyConsole {
             godot_error!("TinyConsole: Can't add autocomplete source: command doesn't exist: {}", cmd);
             return;
         }
-        if argument < 0 || argument > 4 {
+        if !(0..=4).contains(&argument) {
             godot_error!("TinyConsole: Can't add autocomplete source: argument index out of bounds");
             return;
         }
@@ -549,9 +1114,11 @@ impl TinyConsole {
 
     // --- Formatting ---
 
+    /// Formats a command name as a clickable mention: click inserts the command, Shift+click `help <command>`.
     pub(crate) fn format_name(&self, name: GString) -> GString {
         let color = self.output_command_mention_color.to_html();
-        GString::from(format!("[color={}]{}[/color]", color, name).as_str())
+        let name = name.to_string();
+        GString::from(format!("[url={}{}][color={}]{}[/color][/url]", META_COMMAND_PREFIX, util::meta_encode(&name), color, name).as_str())
     }
 
     pub(crate) fn usage(&mut self, command: GString) -> i32 {
@@ -560,7 +1127,7 @@ impl TinyConsole {
         // If it's an alias, show what it resolves to
         if self.aliases.contains_key(&cmd_str) {
             let alias_argv = self.aliases.get(&cmd_str).unwrap().clone();
-            let formatted_cmd_name = format!("[color={}]{}[/color]", self.output_command_mention_color.to_html(), alias_argv[0]);
+            let formatted_cmd_name = self.format_name(GString::from(alias_argv[0].as_str()));
             let rest = alias_argv[1..].join(" ");
             let msg = format!("Alias of: {} {}", formatted_cmd_name, rest);
             self.print_line_internal(&msg, false);
@@ -583,7 +1150,7 @@ impl TinyConsole {
 
         if let Some(ref info) = method_info {
             let required_args = info.args.len().saturating_sub(info.default_count);
-            let bound_args = callable.get_bound_arguments_count() as usize;
+            let bound_args = callable.get_bound_arguments_count();
             let displayable_args = info.args.len().saturating_sub(bound_args);
 
             let mut usage_str = format!("Usage: {}", actual_cmd);
@@ -688,10 +1255,10 @@ impl TinyConsole {
 
     #[func]
     fn on_entry_text_submitted(&mut self, command: GString) {
-        let hg_visible = self.history_gui.as_ref().map_or(false, |hg| hg.is_visible());
+        let hg_visible = self.history_gui.as_ref().is_some_and(|hg| hg.is_visible());
 
         if hg_visible {
-            let current_text = self.history_gui.as_ref().map(|hg| hg.bind().get_current_text().to_string()).unwrap_or_default();
+            let current_text = self.history_gui.as_ref().map(|hg| hg.bind().current_text(self.history.entries())).unwrap_or_default();
             if let Some(ref mut hg) = self.history_gui {
                 hg.set_visible(false);
             }
@@ -702,10 +1269,13 @@ impl TinyConsole {
         }
         self.clear_autocomplete();
         self.fill_entry("");
+        let Some(command) = self.expand_history(&command.to_string()) else {
+            return;
+        };
         // Store command for deferred execution in on_process_frame.
         // We can't call the user's callable here because this #[func]
         // holds bind_mut(), and the callable may call back into TinyConsole.
-        self.pending_command = Some(command.to_string());
+        self.pending_command = Some(command);
     }
 
     #[func]
@@ -717,6 +1287,156 @@ impl TinyConsole {
         } else {
             self.history_iter.reset();
         }
+        if self.history_gui.as_ref().is_some_and(|hg| hg.is_visible()) {
+            self.update_history_search();
+        }
+    }
+
+    #[func]
+    fn on_history_scroll_requested(&mut self, step: i32) {
+        if let Some(ref mut hg) = self.history_gui {
+            if step > 0 {
+                hg.bind_mut().increment_index(self.history.entries());
+            } else {
+                hg.bind_mut().decrement_index(self.history.entries());
+            }
+        }
+    }
+
+    #[func]
+    fn on_history_delete_requested(&mut self) {
+        let command = self.history_gui.as_ref().and_then(|hg| hg.bind().current_command(self.history.entries()));
+        if command.is_some_and(|command| self.history.remove_command(&command)) {
+            self.update_history_search();
+        }
+    }
+
+    #[func]
+    fn on_history_layout_changed(&mut self) {
+        if self.history_gui.as_ref().is_some_and(|hg| hg.is_visible()) {
+            self.update_history_search();
+        }
+    }
+
+    #[func]
+    fn on_find_query_changed(&mut self, query: GString) {
+        let query = query.to_string();
+        self.output_buffer.set_search(&query);
+        let find_filtering = self.find_bar.as_ref().is_some_and(|fb| fb.bind().is_filtering());
+        if find_filtering {
+            let mut filter = self.output_buffer.filter().clone();
+            filter.set_pattern(Some(&query));
+            self.output_buffer.set_filter(filter);
+        }
+    }
+
+    #[func]
+    fn on_find_match_step_requested(&mut self, forward: bool) {
+        self.output_buffer.step_match(forward);
+    }
+
+    #[func]
+    fn on_find_filter_toggled(&mut self, enabled: bool) {
+        let query = self.find_bar.as_ref().map(|fb| fb.bind().query()).unwrap_or_default();
+        let mut filter = self.output_buffer.filter().clone();
+        filter.set_pattern(Some(query.as_str()).filter(|_| enabled));
+        self.output_buffer.set_filter(filter);
+    }
+
+    #[func]
+    fn on_find_closed(&mut self) {
+        self.output_buffer.set_search("");
+        let find_filtering = self.find_bar.as_ref().is_some_and(|fb| fb.bind().is_filtering());
+        if find_filtering {
+            let mut filter = self.output_buffer.filter().clone();
+            filter.set_pattern(None);
+            self.output_buffer.set_filter(filter);
+        }
+        if let Some(ref mut entry) = self.entry {
+            entry.grab_focus();
+        }
+    }
+
+    #[func]
+    fn on_output_meta_clicked(&mut self, meta: Variant) {
+        let meta = meta.to_string();
+        if let Some(location) = meta.strip_prefix(META_SCRIPT_PREFIX) {
+            self.open_script_location(&util::meta_decode(location));
+        } else if let Some(name) = meta.strip_prefix(META_COMMAND_PREFIX) {
+            let name = util::meta_decode(name);
+            if godot::classes::Input::singleton().is_key_pressed(Key::SHIFT) {
+                self.fill_entry(&format!("help {}", name));
+            } else {
+                self.fill_entry(&format!("{} ", name));
+            }
+            self.focus_entry_after_click();
+        } else if let Some(command_line) = meta.strip_prefix(META_RUN_PREFIX) {
+            // Executed in on_process_frame, like a submitted entry
+            self.pending_command = Some(util::meta_decode(command_line));
+        } else if let Some(text) = meta.strip_prefix(META_FILL_PREFIX) {
+            self.fill_entry(&util::meta_decode(text));
+            self.focus_entry_after_click();
+        } else if let Some(fold) = meta.strip_prefix(META_FOLD_PREFIX) {
+            self.toggle_fold(fold);
+        }
+    }
+
+    #[func]
+    fn on_output_menu_id_pressed(&mut self, id: i64) {
+        if id == OUTPUT_MENU_COPY_COMMAND_OUTPUT as i64 {
+            self.copy_command_output(false);
+        }
+    }
+
+    #[func]
+    fn on_channel_tab_changed(&mut self, tab: i64) {
+        let view = if tab > 0 { Some(tab as usize - 1) } else { None };
+        self.output_buffer.set_view(view);
+        self.update_channel_tabs();
+    }
+
+    #[func]
+    fn on_project_settings_changed(&mut self) {
+        self.reload_options();
+    }
+
+    #[func]
+    fn on_window_close_requested(&mut self) {
+        self.close_console();
+    }
+
+    #[func]
+    fn on_resize_grip_input(&mut self, event: Gd<InputEvent>) {
+        if let Ok(button) = event.clone().try_cast::<InputEventMouseButton>() {
+            if button.get_button_index() == MouseButton::LEFT {
+                self.resizing = button.is_pressed();
+                if !self.resizing {
+                    let (key, ratio) = if self.dock.is_side() { ("width_ratio", self.width_ratio) } else { ("height_ratio", self.height_ratio) };
+                    self.user_prefs.set("layout", key, ratio.to_variant());
+                }
+            }
+        } else if let Ok(motion) = event.try_cast::<InputEventMouseMotion>() {
+            if !self.resizing {
+                return;
+            }
+            let Some(viewport) = self.control_block.as_ref().map(|block| block.get_size()) else {
+                return;
+            };
+            let pos = motion.get_global_position() / viewport;
+            let ratio = match self.dock {
+                Dock::Top => pos.y,
+                Dock::Bottom => 1.0 - pos.y,
+                Dock::Left => pos.x,
+                Dock::Right => 1.0 - pos.x,
+                Dock::Window => return,
+            };
+            self.set_size_ratio(ratio);
+        }
+    }
+
+    #[func]
+    fn on_level_toggled(&mut self, _pressed: bool, _level_index: i64) {
+        self.update_visible_levels();
     }
 
     #[func]
@@ -731,7 +1451,7 @@ impl TinyConsole {
 
     #[func]
     fn on_history_up_requested(&mut self) {
-        let prev = self.history_iter.prev();
+        let prev = self.history_iter.prev(&self.history);
         self.fill_entry(&prev);
         self.clear_autocomplete();
         self.update_autocomplete();
@@ -739,7 +1459,7 @@ impl TinyConsole {
 
     #[func]
     fn on_history_down_requested(&mut self) {
-        let next = self.history_iter.next();
+        let next = self.history_iter.next(&self.history);
         self.fill_entry(&next);
         self.clear_autocomplete();
         self.update_autocomplete();
@@ -788,40 +1508,48 @@ impl TinyConsole {
             callable.call_deferred(&[]);
         }
 
+        self.print_engine_logs();
+        self.flush_output();
+        if let Some(ref mut log) = self.session_log {
+            log.flush();
+        }
+        let console_visible = self.control.as_ref().is_some_and(|c| c.is_visible());
+        if let Some(ref mut overlay) = self.overlay {
+            overlay.update(!console_visible);
+        }
+
         // Handle input polling
         self.poll_input();
 
-        // Handle animation
-        let is_processing = self.canvas_layer.as_ref().map_or(false, |cl| cl.is_processing());
+        // Handle animation. Real time, so the slide isn't affected by the game's time scale
+        let now = Time::singleton().get_ticks_usec();
+        let delta = now.saturating_sub(self.last_frame_usec) as f32 / 1_000_000.0;
+        self.last_frame_usec = now;
+        let is_processing = self.canvas_layer.as_ref().is_some_and(|cl| cl.is_processing());
         if !is_processing {
             return;
         }
 
-        let delta = 1.0 / Engine::singleton().get_frames_per_second().max(1.0) as f32;
-        let time_scale = Engine::singleton().get_time_scale() as f32;
         let mut done_sliding = false;
+        // The window dock opens and closes at once
+        let step = if self.dock == Dock::Window { 1.0 } else { self.open_speed * delta };
 
         if self.is_open {
-            self.open_t += self.open_speed * delta * (1.0 / time_scale);
+            self.open_t += step;
             if self.open_t >= 1.0 {
                 self.open_t = 1.0;
                 done_sliding = true;
             }
         } else {
-            self.open_t -= self.open_speed * delta * 1.5 * (1.0 / time_scale);
+            self.open_t -= step * 1.5;
             if self.open_t <= 0.0 {
                 self.open_t = 0.0;
                 done_sliding = true;
-            }
-        }
-
-        let eased = ease(self.open_t as f64, -1.75) as f32;
-        if let Some(ref mut control) = self.control {
-            let height = control.get_size().y;
-            let new_y = remap(eased as f64, 0.0, 1.0, -height as f64, 0.0) as f32;
-            control.set_position(Vector2::new(0.0, new_y));
+            }
         }
 
+        self.slide_panel();
+
         if done_sliding {
             if let Some(ref mut cl) = self.canvas_layer {
                 cl.set_process(false);
@@ -847,8 +1575,8 @@ impl TinyConsole {
             return;
         }
 
-        let control_visible = self.control.as_ref().map_or(false, |c| c.is_visible());
-        let history_visible = self.history_gui.as_ref().map_or(false, |h| h.is_visible());
+        let control_visible = self.control.as_ref().is_some_and(|c| c.is_visible());
+        let history_visible = self.history_gui.as_ref().is_some_and(|h| h.is_visible());
 
         if control_visible && event.is_action_pressed("tiny_console_search_history") {
             self.toggle_history();
@@ -885,23 +1613,289 @@ impl TinyConsole {
     pub(crate) fn get_scene_tree() -> Gd<SceneTree> {
         Engine::singleton().get_main_loop().unwrap().cast::<SceneTree>()
     }
+
+    /// Returns the file path of the current scene, or its node name if it wasn't loaded from a file.
+    fn current_scene_path() -> String {
+        match Self::get_scene_tree().get_current_scene() {
+            Some(scene) => {
+                let path = scene.get_scene_file_path();
+                if path.is_empty() {
+                    scene.get_name().to_string()
+                } else {
+                    path.to_string()
+                }
+            }
+            None => String::new(),
+        }
+    }
 }
 
 // === Private implementation ===
 
 impl TinyConsole {
+    /// Returns the texture to show for `print_image`. Textures loaded from their own file are shown
+    /// as they are; others (viewport textures, textures built at runtime or embedded in a scene)
+    /// can change later, so a snapshot of their current contents is shown instead.
+    fn printable_texture(texture: Gd<Texture2D>, size: Vector2i) -> Option<Gd<Texture2D>> {
+        let path = texture.get_path().to_string();
+        if !path.is_empty() && !path.contains("::") {
+            return Some(texture);
+        }
+        let mut image = texture.get_image()?;
+        if image.is_compressed() {
+            image.decompress();
+        }
+        // Store the snapshot no larger than it is shown
+        let (width, height) = (image.get_width(), image.get_height());
+        let scale = match (size.x > 0, size.y > 0) {
+            (true, true) => (size.x as f32 / width as f32).max(size.y as f32 / height as f32),
+            (true, false) => size.x as f32 / width as f32,
+            (false, true) => size.y as f32 / height as f32,
+            (false, false) => 1.0,
+        };
+        if scale < 1.0 {
+            image.resize(((width as f32 * scale).round() as i32).max(1), ((height as f32 * scale).round() as i32).max(1));
+        }
+        ImageTexture::create_from_image(&image).map(|snapshot| snapshot.upcast())
+    }
+
+    fn print_live_internal(&mut self, text: String, progress: Option<(String, f64)>) -> Gd<LiveLine> {
+        let id = self.output_buffer.next_id();
+        let stdout = self.mirrors_to_stdout(LogLevel::Info);
+        self.print_line_internal(&text, stdout);
+        // A line that wasn't printed can't be updated: its id goes to the next line
+        LiveLine::create(id, text, progress, self.silent)
+    }
+
+    pub(crate) fn update_live_line(&mut self, id: u64, text: &str) {
+        self.output_buffer.replace(id, text);
+        if let Some(ref mut overlay) = self.overlay {
+            overlay.replace(id, text);
+        }
+    }
+
+    pub(crate) fn finish_live_line(&mut self, text: &str) {
+        let stdout = self.mirrors_to_stdout(LogLevel::Info);
+        self.log_line(text, stdout);
+    }
+
+    pub(crate) fn format_progress(&self, label: &str, value: f64) -> String {
+        let filled = (value * PROGRESS_BAR_WIDTH as f64).round() as usize;
+        format!(
+            "{} [color={}]{}[/color][color={}]{}[/color] {:>3}%",
+            label,
+            self.output_command_mention_color.to_html(),
+            "█".repeat(filled),
+            self.output_debug_color.to_html(),
+            "░".repeat(PROGRESS_BAR_WIDTH - filled),
+            (value * 100.0).round()
+        )
+    }
+
+    pub(crate) fn print_variant_internal(&mut self, value: Variant, stdout: bool) {
+        let pretty = PrettyValue::new(value);
+        let id = self.output_buffer.next_id();
+        let text = pretty.render(&self.value_style, &format!("{}{}:", META_FOLD_PREFIX, id));
+        self.print_line_internal(&text, stdout);
+        if pretty.is_foldable() && !self.silent {
+            if self.foldable_values.len() >= MAX_FOLDABLE_VALUES {
+                self.foldable_values.pop_front();
+            }
+            self.foldable_values.push_back((id, pretty));
+        }
+    }
+
+    fn toggle_fold(&mut self, meta: &str) {
+        let Some((id, path)) = meta.split_once(':') else {
+            return;
+        };
+        let (Ok(id), Some(path)) = (id.parse::<u64>(), variant_printer::parse_path(path)) else {
+            return;
+        };
+        let Some((_, pretty)) = self.foldable_values.iter_mut().find(|(value_id, _)| *value_id == id) else {
+            return;
+        };
+        pretty.toggle(path);
+        let text = pretty.render(&self.value_style, &format!("{}{}:", META_FOLD_PREFIX, id));
+        self.output_buffer.replace(id, &text);
+    }
+
+    pub(crate) fn print_table_internal(&mut self, table: &Table, stdout: bool) {
+        for line in table.render() {
+            self.print_line_internal(&line, stdout);
+        }
+    }
+
     pub(crate) fn print_line_internal(&mut self, line: &str, stdout: bool) {
+        self.print_line_level(line, LogLevel::Info, stdout);
+    }
+
+    fn mirrors_to_stdout(&self, level: LogLevel) -> bool {
+        self.options.stdout_levels & level.bit() as i32 != 0
+    }
+
+    pub(crate) fn print_line_level(&mut self, line: &str, level: LogLevel, stdout: bool) {
         if self.silent {
             return;
         }
-        if let Some(ref mut output) = self.output {
-            output.append_text(&GString::from(format!("{}\n", line).as_str()));
-            let line_count = output.get_line_count();
-            output.scroll_to_line(line_count);
+        let id = self.output_buffer.next_id();
+        self.output_buffer.push(line, level, self.output_channel);
+        let console_visible = self.control.as_ref().is_some_and(|c| c.is_visible());
+        if let Some(ref mut overlay) = self.overlay {
+            if !console_visible && level >= self.min_log_level && overlay.accepts(level) {
+                overlay.push(id, line);
+            }
+        }
+        self.log_line(line, stdout);
+    }
+
+    /// Runs `f` with printed lines going to the given channel, which is added if needed.
+    fn with_channel(&mut self, channel: &str, f: impl FnOnce(&mut Self)) {
+        let previous = self.output_channel;
+        self.output_channel = self.output_buffer.channel_id(channel);
+        f(self);
+        self.output_channel = previous;
+    }
+
+    /// Writes a line to the session log and, if `stdout` is set, mirrors it to stdout.
+    fn log_line(&mut self, line: &str, stdout: bool) {
+        if let Some(ref mut log) = self.session_log {
+            log.write_line(&util::bbcode_strip(line));
         }
         if stdout {
-            godot_print!("{}", util::bbcode_strip(line));
+            // Not captured back by the engine logger, which would print the line twice
+            engine_logger::without_capture(|| godot_print!("{}", util::bbcode_strip(line)));
+        }
+    }
+
+    /// Prints messages captured from the engine since the last frame.
+    /// They are already on stdout, so they are never mirrored.
+    fn print_engine_logs(&mut self) {
+        let logs = engine_logger::take_captured();
+        if logs.is_empty() {
+            return;
+        }
+        self.with_channel(ENGINE_CHANNEL, |s| s.print_engine_log_records(logs));
+    }
+
+    fn print_engine_log_records(&mut self, logs: Vec<EngineLog>) {
+        for log in logs {
+            match log {
+                EngineLog::Message { text, error: false } => {
+                    self.print_line_level(&util::bbcode_escape(&text), LogLevel::Info, false);
+                }
+                EngineLog::Message { text, error: true } => {
+                    let msg = format!("[color={}]{}[/color]", self.output_error_color.to_html(), util::bbcode_escape(&text));
+                    self.print_line_level(&msg, LogLevel::Error, false);
+                }
+                EngineLog::Error { error_type, message, function, file, line } => {
+                    let (prefix, color, level) = match error_type {
+                        ErrorType::WARNING => ("WARNING:", self.output_warning_color, LogLevel::Warning),
+                        ErrorType::SCRIPT => ("SCRIPT ERROR:", self.output_error_color, LogLevel::Error),
+                        ErrorType::SHADER => ("SHADER ERROR:", self.output_error_color, LogLevel::Error),
+                        _ => ("ERROR:", self.output_error_color, LogLevel::Error),
+                    };
+                    let location = format!("{}:{}", file, line);
+                    let msg = format!(
+                        "[color={}]{}[/color] {}\n[color={}]   at: {} ([url={}{}][u]{}[/u][/url])[/color]",
+                        color.to_html(),
+                        prefix,
+                        util::bbcode_escape(&message),
+                        self.output_debug_color.to_html(),
+                        util::bbcode_escape(&function),
+                        META_SCRIPT_PREFIX,
+                        util::meta_encode(&location),
+                        util::bbcode_escape(&location)
+                    );
+                    self.print_line_level(&msg, level, false);
+                }
+            }
+        }
+    }
+
+    /// Opens the script of a `path:line` location in the system's default editor.
+    fn open_script_location(&mut self, location: &str) {
+        let path = location.rsplit_once(':').map_or(location, |(path, _)| path);
+        if !FileAccess::file_exists(path) {
+            let msg = format!("Can't open script: {}", path);
+            self.error(GString::from(msg.as_str()));
+            return;
         }
+        let global_path = ProjectSettings::singleton().globalize_path(path);
+        Os::singleton().shell_open(&global_path);
+    }
+
+    /// Shows lines printed since the last frame, and any pending find/filter change, in the output.
+    fn flush_output(&mut self) {
+        let Some(ref mut output) = self.output else {
+            return;
+        };
+        if !self.output_buffer.flush(output) {
+            return;
+        }
+        self.update_channel_tabs();
+        if let Some(ref mut fb) = self.find_bar {
+            if fb.is_visible() {
+                let (current, total) = self.output_buffer.match_info();
+                fb.bind_mut().set_match_info(current, total);
+            }
+        }
+    }
+
+    /// Copies the plain text of the last command's output to the clipboard, or of the command
+    /// before it if `previous` is set (for commands that copy the output of the one before).
+    /// Only lines in the commands channel count. Returns the number of lines copied.
+    pub(crate) fn copy_command_output(&mut self, previous: bool) -> Option<usize> {
+        let ids = if previous { self.previous_command_output.clone() } else { self.command_output_start.map(|start| start..self.output_buffer.next_id()) }?;
+        let lines = self.output_buffer.plain_lines(ids, 0);
+        if lines.is_empty() {
+            return None;
+        }
+        DisplayServer::singleton().clipboard_set(&lines.join("\n"));
+        Some(lines.len())
+    }
+
+    /// Syncs the channel tabs with the channels and their unread counts.
+    /// The tabs are hidden while everything goes to the default channel.
+    fn update_channel_tabs(&mut self) {
+        let Some(ref mut tabs) = self.channel_tabs else {
+            return;
+        };
+        let channels = self.output_buffer.channels();
+        // Called from TinyConsole methods: tab_changed must not re-enter through on_channel_tab_changed
+        tabs.set_block_signals(true);
+        tabs.set_visible(channels.len() > 1);
+        tabs.set_tab_count(channels.len() as i32 + 1);
+        tabs.set_tab_title(0, "All");
+        for (i, channel) in channels.iter().enumerate() {
+            let mut title: String = channel.name.chars().take(1).flat_map(char::to_uppercase).chain(channel.name.chars().skip(1)).collect();
+            if channel.unread > 0 {
+                title.push_str(&format!(" ({})", channel.unread));
+            }
+            tabs.set_tab_title(i as i32 + 1, &title);
+        }
+        tabs.set_current_tab(self.output_buffer.view().map_or(0, |view| view as i32 + 1));
+        tabs.set_block_signals(false);
+    }
+
+    /// Applies the minimum level and the level toggle buttons to the output.
+    fn update_visible_levels(&mut self) {
+        let mut mask = 0;
+        for (i, level) in LogLevel::ALL.into_iter().enumerate() {
+            let allowed = level >= self.min_log_level;
+            if let Some(button) = self.level_buttons.get_mut(i) {
+                button.set_disabled(!allowed);
+                if allowed && button.is_pressed() {
+                    mask |= level.bit();
+                }
+            }
+        }
+        self.output_buffer.set_visible_levels(mask);
+    }
+
+    /// Shows only output lines matching `filter`; an inactive filter shows everything.
+    pub(crate) fn set_output_filter(&mut self, filter: OutputFilter) {
+        self.output_buffer.set_filter(filter);
     }
 
     fn get_entry_text(&self) -> String {
@@ -922,6 +1916,15 @@ impl TinyConsole {
             input_map.action_add_event("tiny_console_toggle", &ev);
         }
 
+        // tiny_console_open_unpaused - Shift+backtick
+        if !input_map.has_action("tiny_console_open_unpaused") {
+            input_map.add_action("tiny_console_open_unpaused");
+            let mut ev = InputEventKey::new_gd();
+            ev.set_keycode(Key::QUOTELEFT);
+            ev.set_shift_pressed(true);
+            input_map.action_add_event("tiny_console_open_unpaused", &ev);
+        }
+
         // tiny_auto_complete_reverse - Shift+Tab
         if !input_map.has_action("tiny_auto_complete_reverse") {
             input_map.add_action("tiny_auto_complete_reverse");
@@ -939,6 +1942,39 @@ impl TinyConsole {
             ev.set_ctrl_pressed(true);
             input_map.action_add_event("tiny_console_search_history", &ev);
         }
+
+        // tiny_console_copy_output - Ctrl+Shift+C
+        if !input_map.has_action("tiny_console_copy_output") {
+            input_map.add_action("tiny_console_copy_output");
+            let mut ev = InputEventKey::new_gd();
+            ev.set_keycode(Key::C);
+            ev.set_ctrl_pressed(true);
+            ev.set_shift_pressed(true);
+            input_map.action_add_event("tiny_console_copy_output", &ev);
+        }
+
+        // tiny_console_font_larger - Ctrl+= (or Ctrl+keypad plus)
+        // tiny_console_font_smaller - Ctrl+- (or Ctrl+keypad minus)
+        for (action, keys) in [("tiny_console_font_larger", [Key::EQUAL, Key::KP_ADD]), ("tiny_console_font_smaller", [Key::MINUS, Key::KP_SUBTRACT])] {
+            if !input_map.has_action(action) {
+                input_map.add_action(action);
+                for key in keys {
+                    let mut ev = InputEventKey::new_gd();
+                    ev.set_keycode(key);
+                    ev.set_ctrl_pressed(true);
+                    input_map.action_add_event(action, &ev);
+                }
+            }
+        }
+
+        // tiny_console_find - Ctrl+F
+        if !input_map.has_action("tiny_console_find") {
+            input_map.add_action("tiny_console_find");
+            let mut ev = InputEventKey::new_gd();
+            ev.set_keycode(Key::F);
+            ev.set_ctrl_pressed(true);
+            input_map.action_add_event("tiny_console_find", &ev);
+        }
     }
 
     fn poll_input(&mut self) {
@@ -949,11 +1985,14 @@ impl TinyConsole {
         // We use Input singleton to check actions each frame
         let input = godot::classes::Input::singleton();
 
-        if input.is_action_just_pressed("tiny_console_toggle") {
+        // Shift+` also matches the plain toggle, so the unpaused open goes first
+        if input.is_action_just_pressed("tiny_console_open_unpaused") && !self.is_open {
+            self.open_console_unpaused();
+        } else if input.is_action_just_pressed("tiny_console_toggle") {
             self.toggle_console();
         }
 
-        let control_visible = self.control.as_ref().map_or(false, |c| c.is_visible());
+        let control_visible = self.control.as_ref().is_some_and(|c| c.is_visible());
         if !control_visible {
             return;
         }
@@ -961,6 +2000,23 @@ impl TinyConsole {
         if input.is_action_just_pressed("tiny_console_search_history") {
             self.toggle_history();
         }
+
+        if input.is_action_just_pressed("tiny_console_find") {
+            if let Some(ref mut fb) = self.find_bar {
+                fb.bind_mut().open();
+            }
+        }
+
+        if input.is_action_just_pressed("tiny_console_copy_output") {
+            self.copy_command_output(false);
+        }
+
+        if input.is_action_just_pressed("tiny_console_font_larger") {
+            self.step_output_font_size(1);
+        }
+        if input.is_action_just_pressed("tiny_console_font_smaller") {
+            self.step_output_font_size(-1);
+        }
     }
 
     fn build_gui(&mut self) {
@@ -970,6 +2026,14 @@ impl TinyConsole {
 
         let cl = self.canvas_layer.as_mut().unwrap();
 
+        // Create overlay (drawn below the console, which covers it while open)
+        if self.options.overlay_enabled {
+            let min_level = LogLevel::from_name(&self.options.overlay_min_level).unwrap_or(LogLevel::Info);
+            let overlay = Overlay::new(self.options.overlay_lines.max(1) as usize, self.options.overlay_duration, min_level);
+            cl.add_child(&overlay.root());
+            self.overlay = Some(overlay);
+        }
+
         // Create control block (to block mouse input)
         let mut con = Control::new_alloc();
         con.set_anchors_preset(LayoutPreset::FULL_RECT);
@@ -978,27 +2042,65 @@ impl TinyConsole {
 
         // Create PanelContainer
         let mut panel = PanelContainer::new_alloc();
-        panel.set_anchor_ex(Side::BOTTOM, self.options.height_ratio).done();
-        panel.set_anchor_ex(Side::RIGHT, 1.0).done();
         cl.add_child(&panel);
 
+        // Create resize grip (placed on the panel's edge by apply_dock)
+        let mut resize_grip = Control::new_alloc();
+        resize_grip.set_mouse_filter(MouseFilter::STOP);
+        cl.add_child(&resize_grip);
+
         // Create VBoxContainer
         let mut vbox = VBoxContainer::new_alloc();
         vbox.set_anchors_preset(LayoutPreset::FULL_RECT);
         panel.add_child(&vbox);
 
+        // Create channel tabs (hidden until a second channel is used)
+        let mut channel_tabs = TabBar::new_alloc();
+        channel_tabs.set_focus_mode(FocusMode::NONE);
+        channel_tabs.set_visible(false);
+        vbox.add_child(&channel_tabs);
+
+        // Create FindBar (hidden until Ctrl+F)
+        let mut find_bar = FindBar::new_alloc();
+        find_bar.set_visible(false);
+        vbox.add_child(&find_bar);
+
         // Create RichTextLabel (output)
         let mut output = RichTextLabel::new_alloc();
         output.set_v_size_flags(SizeFlags::EXPAND_FILL);
         output.set_scroll_active(true);
         output.set_scroll_follow(true);
         output.set_use_bbcode(true);
+        // Command mentions are links too; only actions and locations are underlined explicitly
+        output.set_meta_underline(false);
         output.set_focus_mode(FocusMode::CLICK);
+        output.set_selection_enabled(true);
+        output.set_context_menu_enabled(true);
+        if let Some(mut menu) = output.get_menu() {
+            menu.add_separator();
+            menu.add_item_ex("Copy Last Command Output").id(OUTPUT_MENU_COPY_COMMAND_OUTPUT).done();
+        }
         vbox.add_child(&output);
 
         // Create CommandEntry (input)
-        let entry = CommandEntry::new_alloc();
-        vbox.add_child(&entry);
+        let mut entry = CommandEntry::new_alloc();
+        entry.set_h_size_flags(SizeFlags::EXPAND_FILL);
+
+        // Entry row: input followed by one toggle per log level
+        let mut entry_row = HBoxContainer::new_alloc();
+        entry_row.add_child(&entry);
+        for level in LogLevel::ALL {
+            let mut button = Button::new_alloc();
+            button.set_text(level.name());
+            button.set_tooltip_text(&format!("Show {} lines", level.name()));
+            button.set_toggle_mode(true);
+            button.set_pressed(true);
+            button.set_flat(true);
+            button.set_focus_mode(FocusMode::NONE);
+            entry_row.add_child(&button);
+            self.level_buttons.push(button);
+        }
+        vbox.add_child(&entry_row);
 
         // Set opacity
         panel.set_modulate(Color::from_rgba(1.0, 1.0, 1.0, self.options.opacity));
@@ -1012,22 +2114,202 @@ impl TinyConsole {
         self.output = Some(output);
         self.entry = Some(entry);
         self.history_gui = Some(history_gui);
+        self.find_bar = Some(find_bar);
+        self.channel_tabs = Some(channel_tabs);
+        self.resize_grip = Some(resize_grip);
+        self.apply_dock();
     }
 
-    fn init_theme(&mut self) {
-        let custom_theme_path = GString::from(self.options.custom_theme.as_str());
-        let default_theme_path = GString::from(THEME_DEFAULT);
+    /// Places the panel for the current dock: anchored to an edge of the viewport, or filling
+    /// the console window.
+    fn apply_dock(&mut self) {
+        let Some(mut panel) = self.control.clone() else {
+            return;
+        };
+        let parent: Gd<Node> = match self.dock {
+            Dock::Window => self.console_window().upcast(),
+            _ => match self.canvas_layer.clone() {
+                Some(cl) => cl.upcast(),
+                None => return,
+            },
+        };
+        if panel.get_parent().as_ref() != Some(&parent) {
+            panel.reparent(&parent);
+        }
+
+        let ratio = self.size_ratio();
+        let (left, top, right, bottom) = match self.dock {
+            Dock::Top => (0.0, 0.0, 1.0, ratio),
+            Dock::Bottom => (0.0, 1.0 - ratio, 1.0, 1.0),
+            Dock::Left => (0.0, 0.0, ratio, 1.0),
+            Dock::Right => (1.0 - ratio, 0.0, 1.0, 1.0),
+            Dock::Window => (0.0, 0.0, 1.0, 1.0),
+        };
+        for (side, anchor) in [(Side::LEFT, left), (Side::TOP, top), (Side::RIGHT, right), (Side::BOTTOM, bottom)] {
+            panel.set_anchor(side, anchor);
+        }
+
+        // The grip straddles the edge facing the rest of the screen
+        if let Some(ref mut grip) = self.resize_grip {
+            let (left, top, right, bottom, cursor) = match self.dock {
+                Dock::Top | Dock::Window => (0.0, bottom, 1.0, bottom, CursorShape::VSIZE),
+                Dock::Bottom => (0.0, top, 1.0, top, CursorShape::VSIZE),
+                Dock::Left => (right, 0.0, right, 1.0, CursorShape::HSIZE),
+                Dock::Right => (left, 0.0, left, 1.0, CursorShape::HSIZE),
+            };
+            for (side, anchor) in [(Side::LEFT, left), (Side::TOP, top), (Side::RIGHT, right), (Side::BOTTOM, bottom)] {
+                grip.set_anchor(side, anchor);
+            }
+            grip.set_default_cursor_shape(cursor);
+        }
+        self.slide_panel();
+    }
 
-        let theme: Option<Gd<Theme>> = if ResourceLoader::singleton().exists_ex(&custom_theme_path).type_hint("Theme").done() {
-            ResourceLoader::singleton().load_ex(&custom_theme_path).done().and_then(|r| r.try_cast::<Theme>().ok())
+    /// Returns the panel size ratio of the current dock.
+    fn size_ratio(&self) -> f32 {
+        if self.dock.is_side() {
+            self.width_ratio
         } else {
-            ResourceLoader::singleton().load_ex(&default_theme_path).done().and_then(|r| r.try_cast::<Theme>().ok())
+            self.height_ratio
+        }
+    }
+
+    /// Moves the panel and its resize grip toward the docked edge, as far as the open animation is from done.
+    fn slide_panel(&mut self) {
+        let eased = ease(self.open_t as f64, -1.75) as f32;
+        let Some(ref mut control) = self.control else {
+            return;
+        };
+        let size = control.get_size();
+        let hidden = match self.dock {
+            Dock::Top => Vector2::new(0.0, -size.y),
+            Dock::Bottom => Vector2::new(0.0, size.y),
+            Dock::Left => Vector2::new(-size.x, 0.0),
+            Dock::Right => Vector2::new(size.x, 0.0),
+            Dock::Window => Vector2::ZERO,
+        };
+        let shift = hidden * (1.0 - eased);
+        control.set_offset(Side::LEFT, shift.x);
+        control.set_offset(Side::RIGHT, shift.x);
+        control.set_offset(Side::TOP, shift.y);
+        control.set_offset(Side::BOTTOM, shift.y);
+
+        if let Some(ref mut grip) = self.resize_grip {
+            let half = RESIZE_GRIP_SIZE / 2.0;
+            let extent = if self.dock.is_side() { Vector2::new(half, 0.0) } else { Vector2::new(0.0, half) };
+            grip.set_offset(Side::LEFT, shift.x - extent.x);
+            grip.set_offset(Side::RIGHT, shift.x + extent.x);
+            grip.set_offset(Side::TOP, shift.y - extent.y);
+            grip.set_offset(Side::BOTTOM, shift.y + extent.y);
+        }
+    }
+
+    /// Resizes the panel to `ratio` of the viewport along the dock's axis.
+    fn set_size_ratio(&mut self, ratio: f32) {
+        let ratio = ratio.clamp(MIN_SIZE_RATIO, 1.0);
+        if self.dock.is_side() {
+            self.width_ratio = ratio;
+        } else {
+            self.height_ratio = ratio;
+        }
+        self.apply_dock();
+    }
+
+    /// Returns the font size of the output text.
+    fn current_output_font_size(&self) -> i32 {
+        match &self.output {
+            Some(output) => output.get_theme_font_size("normal_font_size"),
+            None => self.output_font_size.unwrap_or(0),
+        }
+    }
+
+    /// Overrides the output font sizes with `output_font_size`, or goes back to the theme's.
+    fn apply_output_font_size(&mut self) {
+        if let Some(ref mut output) = self.output {
+            for name in ["normal_font_size", "bold_font_size", "italics_font_size", "bold_italics_font_size", "mono_font_size"] {
+                match self.output_font_size {
+                    Some(size) => output.add_theme_font_size_override(name, size),
+                    None => output.remove_theme_font_size_override(name),
+                }
+            }
+        }
+        let font_size = self.current_output_font_size();
+        if font_size > 0 {
+            self.value_style.swatch_size = swatch_size(font_size);
+        }
+    }
+
+    /// Changes the output font size by `delta` and remembers it for later sessions.
+    fn step_output_font_size(&mut self, delta: i32) {
+        let size = (self.current_output_font_size() + delta).clamp(*OUTPUT_FONT_SIZES.start(), *OUTPUT_FONT_SIZES.end());
+        self.output_font_size = Some(size);
+        self.apply_output_font_size();
+        self.user_prefs.set("output", "font_size", size.to_variant());
+    }
+
+    /// Returns the OS window of the window dock, creating it on first use.
+    fn console_window(&mut self) -> Gd<Window> {
+        if let Some(ref window) = self.window {
+            return window.clone();
+        }
+        let main_size = DisplayServer::singleton().window_get_size();
+        let ratio = self.options.height_ratio;
+
+        let mut window = Window::new_alloc();
+        window.set_title("Tiny Console");
+        // Embedded subwindows would be drawn inside the game's viewport
+        window.set_force_native(true);
+        window.set_initial_position(WindowInitialPosition::CENTER_MAIN_WINDOW_SCREEN);
+        window.set_size(Vector2i::new((main_size.x as f32 * ratio) as i32, (main_size.y as f32 * ratio) as i32));
+        window.set_visible(false);
+        window.connect("close_requested", &Callable::from_object_method(&self.to_gd(), "on_window_close_requested"));
+        if let Some(ref mut cl) = self.canvas_layer {
+            cl.add_child(&window);
+        }
+        self.window = Some(window.clone());
+        window
+    }
+
+    /// Shows or hides the panel, along with the input blocker or the console window of the dock.
+    fn set_gui_visible(&mut self, visible: bool) {
+        if let Some(ref mut control) = self.control {
+            control.set_visible(visible);
+        }
+        if let Some(ref mut block) = self.control_block {
+            // The game stays usable beside a separate window
+            block.set_visible(visible && self.dock != Dock::Window);
+        }
+        if let Some(ref mut grip) = self.resize_grip {
+            grip.set_visible(visible && self.dock != Dock::Window);
+        }
+        if let Some(ref mut window) = self.window {
+            window.set_visible(visible && self.dock == Dock::Window);
+            if window.is_visible() {
+                window.grab_focus();
+            }
+        }
+    }
+
+    fn init_theme(&mut self) {
+        let chosen_theme = match self.theme_name.as_str() {
+            "" => None,
+            name => {
+                let theme = load_theme_by_name(name);
+                if theme.is_none() {
+                    godot_warn!("TinyConsole: Can't load theme {}; using the project's theme.", name);
+                }
+                theme
+            }
         };
+        let theme = chosen_theme.or_else(|| load_theme_file(&self.options.custom_theme)).or_else(|| load_theme_file(THEME_DEFAULT));
 
         if let Some(theme) = theme {
             if let Some(ref mut panel) = self.control {
                 panel.set_theme(&theme);
             }
+            if let Some(ref overlay) = self.overlay {
+                overlay.root().set_theme(&theme);
+            }
 
             let ctype = &StringName::from(CONSOLE_COLORS_THEME_TYPE);
             self.output_command_color = theme.get_color(&StringName::from("output_command_color"), ctype);
@@ -1042,6 +2324,42 @@ impl TinyConsole {
             self.entry_subcommand_color = theme.get_color(&StringName::from("entry_subcommand_color"), ctype);
             self.entry_command_not_found_color = theme.get_color(&StringName::from("entry_command_not_found_color"), ctype);
 
+            self.output_buffer.set_prefix_color(self.output_debug_color);
+
+            // Value colors are optional in custom themes
+            let mut value_style = ValueStyle { punctuation: self.output_debug_color, ..ValueStyle::default() };
+            for (name, color) in [
+                ("output_value_string_color", &mut value_style.string),
+                ("output_value_number_color", &mut value_style.number),
+                ("output_value_keyword_color", &mut value_style.keyword),
+                ("output_value_type_color", &mut value_style.type_name),
+            ] {
+                let name = StringName::from(name);
+                if theme.has_color(&name, ctype) {
+                    *color = theme.get_color(&name, ctype);
+                }
+            }
+            if ResourceLoader::singleton().exists(SWATCH_TEXTURE) {
+                value_style.swatch_image = SWATCH_TEXTURE.to_string();
+                let font_size = theme.get_font_size(&StringName::from("normal_font_size"), &StringName::from("RichTextLabel"));
+                let font_size = if font_size > 0 { font_size } else { theme.get_default_font_size() };
+                value_style.swatch_size = swatch_size(self.output_font_size.unwrap_or(font_size));
+            }
+            self.value_style = value_style;
+
+            // Find highlight colors are optional in custom themes
+            let (match_name, current_name) = (StringName::from("output_find_match_color"), StringName::from("output_find_current_color"));
+            if theme.has_color(&match_name, ctype) && theme.has_color(&current_name, ctype) {
+                self.output_buffer.set_match_colors(theme.get_color(&match_name, ctype), theme.get_color(&current_name, ctype));
+            }
+
+            // Level toggles use the matching message colors
+            let level_colors = [self.output_debug_color, self.output_text_color, self.output_warning_color, self.output_error_color];
+            for (button, color) in self.level_buttons.iter_mut().zip(level_colors) {
+                button.add_theme_color_override("font_pressed_color", color);
+                button.add_theme_color_override("font_hover_pressed_color", color);
+            }
+
             // Apply to output
             if let Some(ref mut output) = self.output {
                 output.add_theme_color_override("default_color", self.output_text_color);
@@ -1086,7 +2404,7 @@ impl TinyConsole {
 
         let color = self.output_command_mention_color.to_html();
         let debug_color = self.output_debug_color.to_html();
-        let tip1 = format!("[i][color={}]Type [color={}]commands[/color] to list all available commands.[/color][/i]", debug_color, color);
+        let tip1 = format!("[i][color={}]Type {} to list all available commands.[/color][/i]", debug_color, self.format_name("commands".into()));
         self.print_line_internal(&tip1, false);
         let tip2 = format!("[i][color={}]Type [color={}]help command[/color] to get more info about the command.[/color][/i]", debug_color, color);
         self.print_line_internal(&tip2, false);
@@ -1184,10 +2502,31 @@ impl TinyConsole {
         result
     }
 
+    /// Applies history expansion (`!!`, `!n`, `^old^new`) to a line typed into the command entry.
+    /// Lines run from scripts or `execute_command()` are never expanded. Prints an error and
+    /// returns `None` if a designator can't be resolved.
+    fn expand_history(&mut self, command_line: &str) -> Option<String> {
+        let trimmed = command_line.trim_start();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            return Some(command_line.to_string());
+        }
+        // The leading space is kept, as it keeps the command out of history
+        let leading_space = &command_line[..command_line.len() - trimmed.len()];
+        match self.history.expand(trimmed) {
+            Ok(expanded) => Some(format!("{}{}", leading_space, expanded)),
+            Err(msg) => {
+                self.error(GString::from(msg.as_str()));
+                None
+            }
+        }
+    }
+
     /// Prepares a command for execution: parses, validates, echoes to output.
-    /// Returns `Some((callable, args, argv))` if a user callable should be invoked,
-    /// or `None` if the command was handled entirely (error, empty, etc.).
-    fn prepare_command(&mut self, command_line: &str, silent: bool) -> Option<(Callable, VarArray, Vec<String>)> {
+    /// Returns `Some((callable, args, argv, history_line))` if a user callable should be invoked,
+    /// or `None` if the command was handled entirely (error, empty, etc.). `history_line` is the
+    /// line to add to history once the command's exit status is known.
+    fn prepare_command(&mut self, command_line: &str, silent: bool) -> Option<(Callable, VarArray, Vec<String>, Option<String>)> {
+        let has_leading_space = command_line.starts_with(' ');
         let command_line = command_line.trim();
         if command_line.is_empty() || command_line.starts_with('#') {
             return None;
@@ -1204,15 +2543,19 @@ impl TinyConsole {
         let command_name = expanded_argv[0].clone();
 
         self.silent = silent;
+        let mut history_line = None;
         if !silent {
-            let history_line = argv.join(" ");
-            self.history.push_entry(history_line);
-            self.history.reassign_iterator(&mut self.history_iter);
+            if !(has_leading_space && self.history.filter().ignore_space) {
+                history_line = Some(argv.join(" "));
+            }
 
             let color = self.output_command_color.to_html();
             let rest = argv[1..].join(" ");
             let msg = format!("[color={}][b]>[/b] {}[/color] {}", color, argv[0], rest);
+            let echo_id = self.output_buffer.next_id();
             self.print_line_internal(&msg, false);
+            self.previous_command_output = self.command_output_start.map(|start| start..echo_id);
+            self.command_output_start = Some(echo_id + 1);
         }
 
         if !self.commands.contains_key(&command_name) {
@@ -1221,6 +2564,7 @@ impl TinyConsole {
             let err_msg = format!("[color={}]ERROR:[/color] {}", color, msg);
             self.print_line_internal(&err_msg, false);
             self.suggest_similar_command(&expanded_argv);
+            self.record_history(history_line, EXIT_COMMAND_NOT_FOUND);
             self.silent = false;
             return None;
         }
@@ -1230,9 +2574,10 @@ impl TinyConsole {
 
         let call_args = self.parse_argv(&expanded_argv, &callable, &method_info);
         match call_args {
-            Some(args) => Some((callable, args, expanded_argv)),
+            Some(args) => Some((callable, args, expanded_argv, history_line)),
             None => {
                 self.usage(GString::from(argv[0].as_str()));
+                self.record_history(history_line, EXIT_BAD_ARGUMENTS);
                 self.silent = false;
                 None
             }
@@ -1240,12 +2585,12 @@ impl TinyConsole {
     }
 
     /// Called after the user callable has been invoked (outside the mutable borrow).
-    fn finish_command(&mut self, result: &Variant, expanded_argv: &[String]) {
-        if let Ok(err_code) = result.try_to::<i32>() {
-            if err_code > 0 {
-                self.suggest_argument_corrections(expanded_argv);
-            }
+    fn finish_command(&mut self, result: &Variant, expanded_argv: &[String], history_line: Option<String>) {
+        let exit_code = result.try_to::<i32>().unwrap_or(0);
+        if exit_code > 0 {
+            self.suggest_argument_corrections(expanded_argv);
         }
+        self.record_history(history_line, exit_code);
 
         if self.options.sparse_mode {
             self.print_line_internal("", false);
@@ -1253,6 +2598,13 @@ impl TinyConsole {
         self.silent = false;
     }
 
+    /// Adds an interactive command line to history, subject to the history filter.
+    fn record_history(&mut self, line: Option<String>, exit_code: i32) {
+        if let Some(line) = line {
+            self.history.push_entry(line, Self::current_scene_path(), exit_code);
+        }
+    }
+
     /// Executes a command, properly releasing the mutable borrow before calling
     /// the user's callable (which may call back into TinyConsole).
     /// Must be called on a `Gd<TinyConsole>`, not on `&mut self`.
@@ -1260,12 +2612,12 @@ impl TinyConsole {
         let pending = this.bind_mut().prepare_command(command_line, silent);
         // bind_mut() is dropped here — self is no longer borrowed
 
-        if let Some((callable, args, expanded_argv)) = pending {
+        if let Some((callable, args, expanded_argv, history_line)) = pending {
             // Safe: the singleton is not borrowed during callv
             let result = callable.callv(&args);
 
             // Re-borrow to finish up
-            this.bind_mut().finish_command(&result, &expanded_argv);
+            this.bind_mut().finish_command(&result, &expanded_argv, history_line);
         }
     }
 
@@ -1281,7 +2633,7 @@ impl TinyConsole {
             }
         };
 
-        let bound_args = callable.get_bound_arguments_count() as usize;
+        let bound_args = callable.get_bound_arguments_count();
         let num_args = argv.len() + bound_args - 1;
         let max_args = info.args.len();
         let required_args = max_args.saturating_sub(info.default_count);
@@ -1530,10 +2882,9 @@ impl TinyConsole {
     fn add_history_autocompletes(&mut self) {
         if self.options.autocomplete_use_history_with_matches || self.autocomplete_matches.is_empty() {
             let entry_text = self.get_entry_text();
-            let entries = self.history.entries().to_vec();
-            for entry in entries.iter().rev() {
-                if entry.starts_with(&entry_text) {
-                    self.autocomplete_matches.push(entry.clone());
+            for entry in self.history.entries().iter().rev() {
+                if entry.command.starts_with(&entry_text) {
+                    self.autocomplete_matches.push(entry.command.clone());
                 }
             }
         }
@@ -1587,14 +2938,21 @@ impl TinyConsole {
         }
         let all_names = self.get_all_command_names_with_aliases();
         if let Some(fuzzy_hit) = util::fuzzy_match_string(&argv[0], 2, &all_names) {
+            let mut suggest = argv.to_vec();
+            suggest[0] = fuzzy_hit.clone();
+            let suggest_command = suggest.join(" ").trim().to_string();
+
             let color = self.output_command_mention_color.to_html();
             let debug_color = self.output_debug_color.to_html();
-            let tip = format!("[i][color={}]Did you mean [color={}]{}[/color]? ([b]TAB[/b] to fill)[/color][/i]", debug_color, color, fuzzy_hit);
+            let tip = format!(
+                "[i][color={}]Did you mean [url={}{}][color={}]{}[/color][/url]? (click or [b]TAB[/b] to fill)[/color][/i]",
+                debug_color,
+                META_FILL_PREFIX,
+                util::meta_encode(&suggest_command),
+                color,
+                fuzzy_hit
+            );
             self.print_line_internal(&tip, false);
-
-            let mut suggest = argv.to_vec();
-            suggest[0] = fuzzy_hit;
-            let suggest_command = suggest.join(" ").trim().to_string();
             self.autocomplete_matches.push(suggest_command);
         }
     }
@@ -1628,12 +2986,17 @@ impl TinyConsole {
             let color = self.output_command_mention_color.to_html();
             let debug_color = self.output_debug_color.to_html();
             let args_str = corrected_argv[1..].join(" ");
+            let suggest = corrected_argv.join(" ").trim().to_string();
             let tip = format!(
-                "[i][color={}]Did you mean \"[color={}]{}[/color] {}\"? ([b]TAB[/b] to fill)[/color][/i]",
-                debug_color, color, actual_cmd, args_str
+                "[i][color={}]Did you mean \"[url={}{}][color={}]{}[/color] {}[/url]\"? (click or [b]TAB[/b] to fill)[/color][/i]",
+                debug_color,
+                META_FILL_PREFIX,
+                util::meta_encode(&suggest),
+                color,
+                actual_cmd,
+                util::bbcode_escape(&args_str)
             );
             self.print_line_internal(&tip, false);
-            let suggest = corrected_argv.join(" ").trim().to_string();
             self.autocomplete_matches.push(suggest);
         }
     }
@@ -1646,6 +3009,21 @@ impl TinyConsole {
         if command == "help" && arg_index == 0 {
             return Some(self.get_all_command_names_with_aliases());
         }
+        if command == "loglevel" && arg_index == 0 {
+            return Some(LogLevel::ALL.iter().map(|l| l.name().to_string()).collect());
+        }
+        if command == "pause_mode" && arg_index == 0 {
+            return Some(PauseMode::SETTING_VALUES.split(',').map(String::from).collect());
+        }
+        if command == "theme" && arg_index == 0 {
+            return Some(self.get_theme_presets().as_slice().iter().map(|name| name.to_string()).collect());
+        }
+        if command == "dock" && arg_index == 0 {
+            return Some(Dock::SETTING_VALUES.split(',').map(String::from).collect());
+        }
+        if command == "line_prefix" && arg_index == 0 {
+            return Some(LinePrefix::SETTING_VALUES.split(',').map(String::from).collect());
+        }
         let key = (command.to_string(), arg_index);
         if let Some(source) = self.argument_autocomplete_sources.get(&key).cloned() {
             let result = source.callv(&VarArray::new());
@@ -1664,21 +3042,17 @@ impl TinyConsole {
     // --- Console show/hide ---
 
     fn show_console(&mut self) {
-        let is_visible = self.control.as_ref().map_or(false, |c| c.is_visible());
+        let is_visible = self.control.as_ref().is_some_and(|c| c.is_visible());
         if !is_visible && self.enabled {
-            if let Some(ref mut control) = self.control {
-                control.set_visible(true);
-            }
-            if let Some(ref mut block) = self.control_block {
-                block.set_visible(true);
+            self.set_gui_visible(true);
+            // Everything in the overlay is in the output too
+            if let Some(ref mut overlay) = self.overlay {
+                overlay.clear();
             }
 
-            if self.options.pause_when_open {
-                let mut tree = Self::get_scene_tree();
-                self.was_already_paused = tree.is_paused();
-                if !self.was_already_paused {
-                    tree.set_pause(true);
-                }
+            // Already set when opened unpaused
+            if self.applied_pause.is_none() {
+                self.apply_pause();
             }
 
             let tree = Self::get_scene_tree();
@@ -1695,19 +3069,11 @@ impl TinyConsole {
     }
 
     fn hide_console(&mut self) {
-        let is_visible = self.control.as_ref().map_or(false, |c| c.is_visible());
+        let is_visible = self.control.as_ref().is_some_and(|c| c.is_visible());
         if is_visible {
-            if let Some(ref mut control) = self.control {
-                control.set_visible(false);
-            }
-            if let Some(ref mut block) = self.control_block {
-                block.set_visible(false);
-            }
+            self.set_gui_visible(false);
 
-            if self.options.pause_when_open && !self.was_already_paused {
-                let mut tree = Self::get_scene_tree();
-                tree.set_pause(false);
-            }
+            self.release_pause();
 
             if let Some(ref prev) = self.previous_gui_focus {
                 if prev.is_instance_valid() {
@@ -1720,6 +3086,49 @@ impl TinyConsole {
         }
     }
 
+    /// Pauses or slows down the game as the pause mode says, remembering how to undo it.
+    fn apply_pause(&mut self) {
+        self.applied_pause = match self.pause_mode {
+            PauseMode::None => None,
+            PauseMode::Pause | PauseMode::PauseKeepPhysicsServers => {
+                let mut tree = Self::get_scene_tree();
+                let was_paused = tree.is_paused();
+                if !was_paused {
+                    tree.set_pause(true);
+                    if self.pause_mode == PauseMode::PauseKeepPhysicsServers {
+                        // Pausing the tree deactivates the physics servers. Nodes stay paused,
+                        // so only what the servers simulate on their own keeps moving.
+                        PhysicsServer2D::singleton().set_active(true);
+                        PhysicsServer3D::singleton().set_active(true);
+                    }
+                }
+                Some(AppliedPause::Tree { was_paused })
+            }
+            PauseMode::SlowMotion => {
+                let mut engine = Engine::singleton();
+                let previous = engine.get_time_scale();
+                engine.set_time_scale(self.options.slow_motion_scale as f64);
+                Some(AppliedPause::TimeScale { previous })
+            }
+        };
+    }
+
+    fn release_pause(&mut self) {
+        match self.applied_pause.take() {
+            Some(AppliedPause::Tree { was_paused: false }) => Self::get_scene_tree().set_pause(false),
+            Some(AppliedPause::TimeScale { previous }) => Engine::singleton().set_time_scale(previous),
+            _ => {}
+        }
+    }
+
+    fn focus_entry_after_click(&mut self) {
+        self.clear_autocomplete();
+        self.update_autocomplete();
+        if let Some(ref mut entry) = self.entry {
+            entry.grab_focus();
+        }
+    }
+
     fn fill_entry(&mut self, line: &str) {
         if let Some(ref mut entry) = self.entry {
             entry.set_text(&GString::from(line));
@@ -1738,12 +3147,12 @@ impl TinyConsole {
         let mut handled = true;
 
         if keycode == Key::UP {
-            let prev = self.history_iter.prev();
+            let prev = self.history_iter.prev(&self.history);
             self.fill_entry(&prev);
             self.clear_autocomplete();
             self.update_autocomplete();
         } else if keycode == Key::DOWN {
-            let next = self.history_iter.next();
+            let next = self.history_iter.next(&self.history);
             self.fill_entry(&next);
             self.clear_autocomplete();
             self.update_autocomplete();
@@ -1779,6 +3188,15 @@ impl TinyConsole {
         }
     }
 
+    /// Filters the history search panel by the current input text.
+    fn update_history_search(&mut self) {
+        let entry_text = self.get_entry_text();
+        let results = self.history.fuzzy_match(&entry_text).to_vec();
+        if let Some(ref mut hg) = self.history_gui {
+            hg.bind_mut().set_search_results(&entry_text, results, self.history.entries());
+        }
+    }
+
     fn handle_history_input(&mut self, event: &Gd<InputEventKey>) {
         if event.is_action_pressed("tiny_auto_complete_reverse") {
             self.reverse_autocomplete();
@@ -1792,14 +3210,8 @@ impl TinyConsole {
             if let Some(mut vp) = tree.get_root() {
                 vp.set_input_as_handled();
             }
-        } else {
-            let entry_text = self.get_entry_text();
-            let results = self.history.fuzzy_match(&entry_text);
-            let packed: PackedStringArray = results.iter().map(|s| GString::from(s.as_str())).collect();
-            if let Some(ref mut hg) = self.history_gui {
-                hg.bind_mut().set_search_results(packed);
-            }
         }
+        // Typed characters refresh the results from on_entry_text_changed, once the text has changed
 
         if let Some(ref mut entry) = self.entry {
             entry.grab_focus();
@@ -1809,17 +3221,26 @@ impl TinyConsole {
     pub fn cleanup(&mut self) {
         if self.options.persist_history {
             self.history.trim(self.options.history_lines as usize);
-            self.history.save(command_history::HISTORY_FILE);
+            self.history.save(&self.history_file);
         }
 
         self.initialized = false;
 
+        if let Some(logger) = self.engine_logger.take() {
+            Os::singleton().remove_logger(&logger);
+        }
+
         // Disconnect from scene tree
         let this = self.to_gd();
         let mut tree = Self::get_scene_tree();
         if tree.is_connected("process_frame", &Callable::from_object_method(&this, "on_process_frame")) {
             tree.disconnect("process_frame", &Callable::from_object_method(&this, "on_process_frame"));
         }
+        let mut project_settings = ProjectSettings::singleton();
+        let settings_changed = Callable::from_object_method(&this, "on_project_settings_changed");
+        if project_settings.is_connected("settings_changed", &settings_changed) {
+            project_settings.disconnect("settings_changed", &settings_changed);
+        }
 
         // Drop builtin commands object before clearing callable collections
         self.builtin_commands = None;
@@ -1835,8 +3256,13 @@ impl TinyConsole {
         self.entry = None;
         self.output = None;
         self.control = None;
+        self.window = None;
+        self.resize_grip = None;
+        self.overlay = None;
         self.control_block = None;
         self.history_gui = None;
+        self.find_bar = None;
+        self.level_buttons.clear();
         self.previous_gui_focus = None;
 
         // Remove canvas layer from tree and free it immediately.
@@ -1863,20 +3289,52 @@ impl IObject for TinyConsole {
         options.load_from_project_settings();
 
         let mut history = CommandHistory::new();
+        history.set_filter(HistoryFilter::new(
+            options.history_ignore_space,
+            DuplicateMode::from_setting(&options.history_duplicates),
+            options.history_ignore_failed,
+            &options.history_ignore_patterns,
+        ));
+        let history_profile = match command_history::profile_from_cmdline() {
+            Some(profile) if command_history::is_valid_profile_name(&profile) => profile,
+            Some(profile) => {
+                godot_error!("TinyConsole: Invalid history profile name: {}. Use letters, digits, '_' and '-'.", profile);
+                String::new()
+            }
+            None => String::new(),
+        };
+        let history_file = command_history::profile_path(&options.history_file, &history_profile);
         if options.persist_history {
-            history.load(command_history::HISTORY_FILE);
-        }
-        let history_iter = history.create_iterator();
+            history.load(&history_file);
+        }
+        let history_iter = HistoryCursor::new();
+        let min_log_level = LogLevel::from_name(&options.min_log_level).unwrap_or(LogLevel::Debug);
+        let mut output_buffer = OutputBuffer::new(options.scrollback_lines.max(1) as usize, COMMANDS_CHANNEL);
+        output_buffer.set_prefix(LinePrefix::from_name(&options.line_prefix).unwrap_or(LinePrefix::None));
+        let dock = Dock::from_name(&options.dock).unwrap_or(Dock::Top);
+        let pause_mode = PauseMode::from_name(&options.pause_mode).unwrap_or(PauseMode::Pause);
+        let user_prefs = UserPrefs::load(DEFAULT_PREFS_FILE);
+        let height_ratio = user_prefs.get_float("layout", "height_ratio").unwrap_or(options.height_ratio);
+        let width_ratio = user_prefs.get_float("layout", "width_ratio").unwrap_or(options.width_ratio);
+        let output_font_size = user_prefs.get_int("output", "font_size");
+        let theme_name = user_prefs.get_string("appearance", "theme").unwrap_or_default();
 
         Self {
             base,
             canvas_layer: None,
 
             control: None,
+            window: None,
+            resize_grip: None,
             control_block: None,
             output: None,
+            output_buffer,
             entry: None,
             history_gui: None,
+            find_bar: None,
+            channel_tabs: None,
+            overlay: None,
+            level_buttons: Vec::new(),
             previous_gui_focus: None,
 
             output_command_color: Color::from_rgba(0.6, 0.85, 0.45, 1.0),
@@ -1890,6 +3348,7 @@ impl IObject for TinyConsole {
             entry_command_found_color: Color::from_rgba(0.6, 0.85, 0.45, 1.0),
             entry_subcommand_color: Color::from_rgba(0.337, 0.62, 1.0, 1.0),
             entry_command_not_found_color: Color::from_rgba(1.0, 0.3, 0.3, 1.0),
+            value_style: ValueStyle::default(),
 
             enabled: true,
             initialized: false,
@@ -1900,13 +3359,31 @@ impl IObject for TinyConsole {
             argument_autocomplete_sources: HashMap::new(),
             history,
             history_iter,
+            history_profile,
+            history_file,
+            min_log_level,
+            output_channel: 0,
+            command_output_start: None,
+            previous_command_output: None,
+            engine_logger: None,
+            session_log: None,
+            foldable_values: VecDeque::new(),
             autocomplete_matches: Vec::new(),
             eval_inputs: HashMap::new(),
             silent: false,
-            was_already_paused: false,
+            pause_mode,
+            applied_pause: None,
             open_t: 0.0,
+            last_frame_usec: Time::singleton().get_ticks_usec(),
             open_speed: 5.0,
             is_open: false,
+            dock,
+            height_ratio,
+            width_ratio,
+            resizing: false,
+            output_font_size,
+            theme_name,
+            user_prefs,
             pending_command: None,
             builtin_commands: None,
         }
@@ -1954,6 +3431,27 @@ fn parse_method_dict(dict: &VarDictionary) -> MethodInfo {
     }
 }
 
+/// Loads a Theme resource, or returns `None` if there is none at `path`.
+fn load_theme_file(path: &str) -> Option<Gd<Theme>> {
+    if !ResourceLoader::singleton().exists_ex(path).type_hint("Theme").done() {
+        return None;
+    }
+    ResourceLoader::singleton().load_ex(path).done().and_then(|r| r.try_cast::<Theme>().ok())
+}
+
+/// Builds a theme preset by name, or loads the Theme resource at a path.
+fn load_theme_by_name(name: &str) -> Option<Gd<Theme>> {
+    match ThemePreset::from_name(name) {
+        Some(preset) => preset.build(&load_theme_file(THEME_DEFAULT)?),
+        None => load_theme_file(name),
+    }
+}
+
+/// Side of the color swatches printed with values, for an output font size.
+fn swatch_size(font_size: i32) -> i32 {
+    (font_size as f32 * 0.8).round().max(8.0) as i32
+}
+
 /// Extract string elements from a Variant that holds an Array.
 /// Works with both typed (Array[String]) and untyped (Array) arrays,
 /// avoiding the gdext 0.4.x issue where try_to::<VarArray>() fails
@@ -0,0 +1,55 @@
+/// UserPrefs: Console settings changed by the player at runtime, kept across sessions.
+/// Stored as a ConfigFile in `user://`, so they override the project defaults per user
+/// without touching ProjectSettings.
+use godot::classes::ConfigFile;
+use godot::global::Error;
+use godot::prelude::*;
+
+pub const DEFAULT_PREFS_FILE: &str = "user://tiny_console.cfg";
+
+pub struct UserPrefs {
+    path: String,
+    config: Gd<ConfigFile>,
+}
+
+impl UserPrefs {
+    /// Reads the preferences at `path`. A missing or unreadable file gives empty preferences.
+    pub fn load(path: &str) -> Self {
+        let mut config = ConfigFile::new_gd();
+        // A missing file is the normal first-run case; anything else is worth a note
+        let err = config.load(path);
+        if err != Error::OK && err != Error::ERR_FILE_NOT_FOUND {
+            godot_warn!("TinyConsole: Failed to read user preferences from {}: {:?}", path, err);
+        }
+        Self { path: path.to_string(), config }
+    }
+
+    pub fn get_float(&self, section: &str, key: &str) -> Option<f32> {
+        let value = self.config.get_value_ex(section, key).done();
+        value.try_to::<f64>().ok().map(|v| v as f32)
+    }
+
+    pub fn get_int(&self, section: &str, key: &str) -> Option<i32> {
+        let value = self.config.get_value_ex(section, key).done();
+        value.try_to::<i64>().ok().map(|v| v as i32)
+    }
+
+    pub fn get_string(&self, section: &str, key: &str) -> Option<String> {
+        let value = self.config.get_value_ex(section, key).done();
+        value.try_to::<GString>().ok().map(|v| v.to_string())
+    }
+
+    /// Sets a value and writes the file. A nil value removes the key.
+    pub fn set(&mut self, section: &str, key: &str, value: Variant) {
+        if value.is_nil() {
+            if self.config.has_section_key(section, key) {
+                self.config.erase_section_key(section, key);
+            }
+        } else {
+            self.config.set_value(section, key, &value);
+        }
+        if self.config.save(&self.path) != Error::OK {
+            godot_error!("TinyConsole: Failed to save user preferences to file: {}", self.path);
+        }
+    }
+}
@@ -1,4 +1,6 @@
-/// Utility functions: BBCode processing, fuzzy matching, validation.
+//! Utility functions: BBCode processing, fuzzy matching, validation.
+use godot::classes::Time;
+use godot::prelude::*;
 
 /// Escapes BBCode brackets in text for safe display in RichTextLabel.
 pub fn bbcode_escape(text: &str) -> String {
@@ -8,21 +10,143 @@ pub fn bbcode_escape(text: &str) -> String {
         .replace("~RB~", "[rb]")
 }
 
-/// Strips all BBCode tags from text, returning plain text.
+/// Strips all BBCode tags from text, returning plain text. The `[lb]` and `[rb]` escapes
+/// become brackets again.
 pub fn bbcode_strip(text: &str) -> String {
     let mut result = String::with_capacity(text.len());
-    let mut in_brackets = false;
-    for c in text.chars() {
-        match c {
-            '[' => in_brackets = true,
-            ']' => in_brackets = false,
-            _ if !in_brackets => result.push(c),
-            _ => {}
+    let mut rest = text;
+    while let Some(open) = rest.find('[') {
+        result.push_str(&rest[..open]);
+        let Some(len) = bbcode_tag_len(&rest[open..]) else {
+            return result;
+        };
+        if let Some(bracket) = bbcode_escaped_bracket(&rest[open..open + len]) {
+            result.push(bracket);
         }
+        rest = &rest[open + len..];
     }
+    result.push_str(rest);
     result
 }
 
+/// Returns the bracket a `[lb]` or `[rb]` tag stands for.
+fn bbcode_escaped_bracket(tag: &str) -> Option<char> {
+    match tag {
+        "[lb]" => Some('['),
+        "[rb]" => Some(']'),
+        _ => None,
+    }
+}
+
+/// Returns the byte length of the tag at the start of `text` (which starts with `[`),
+/// or `None` if it isn't closed. An `[img]` tag extends to its closing tag, as the image
+/// path between them isn't displayed.
+fn bbcode_tag_len(text: &str) -> Option<usize> {
+    let close = text.find(']')? + 1;
+    let name = text[1..close - 1].split([' ', '=']).next().unwrap_or("");
+    if name == "img" {
+        if let Some(end) = text.find("[/img]") {
+            return Some(end + "[/img]".len());
+        }
+    }
+    Some(close)
+}
+
+/// Returns the number of characters BBCode text takes up on screen.
+/// Tags don't count; the `[lb]` and `[rb]` escapes count as one character each.
+pub fn bbcode_width(text: &str) -> usize {
+    let mut width = 0;
+    let mut rest = text;
+    while let Some(open) = rest.find('[') {
+        width += rest[..open].chars().count();
+        let Some(len) = bbcode_tag_len(&rest[open..]) else {
+            return width;
+        };
+        if bbcode_escaped_bracket(&rest[open..open + len]).is_some() {
+            width += 1;
+        }
+        rest = &rest[open + len..];
+    }
+    width + rest.chars().count()
+}
+
+/// Wraps character ranges of the tag-stripped text (as returned by `bbcode_strip`) in `[bgcolor]` tags.
+/// Ranges are `(start, end, color)` in characters, sorted and non-overlapping. A highlight is closed
+/// around any tag it spans, so it never breaks the nesting of the original tags.
+pub fn bbcode_highlight(text: &str, ranges: &[(usize, usize, Color)]) -> String {
+    let mut highlighter = Highlighter { result: String::with_capacity(text.len() + ranges.len() * 32), ranges, pos: 0, open: None };
+    let mut rest = text;
+    loop {
+        let tag_start = rest.find('[');
+        let mut buf = [0; 4];
+        for c in rest[..tag_start.unwrap_or(rest.len())].chars() {
+            highlighter.push_char(c.encode_utf8(&mut buf));
+        }
+        let Some(tag_start) = tag_start else {
+            break;
+        };
+        let len = bbcode_tag_len(&rest[tag_start..]).unwrap_or(rest.len() - tag_start);
+        let tag = &rest[tag_start..tag_start + len];
+        if bbcode_escaped_bracket(tag).is_some() {
+            highlighter.push_char(tag);
+        } else {
+            highlighter.push_tag(tag);
+        }
+        rest = &rest[tag_start + len..];
+    }
+    if highlighter.open.is_some() {
+        highlighter.result.push_str("[/bgcolor]");
+    }
+    highlighter.result
+}
+
+struct Highlighter<'a> {
+    result: String,
+    /// Ranges not yet closed, in order.
+    ranges: &'a [(usize, usize, Color)],
+    /// Displayed characters so far.
+    pos: usize,
+    open: Option<Color>,
+}
+
+impl Highlighter<'_> {
+    /// Adds one displayed character (or bracket escape), opening and closing highlights around it.
+    fn push_char(&mut self, c: &str) {
+        if let Some(&(start, _, color)) = self.ranges.first() {
+            if start == self.pos {
+                self.result.push_str(&format!("[bgcolor={}]", color.to_html()));
+                self.open = Some(color);
+            }
+        }
+        self.result.push_str(c);
+        self.pos += 1;
+        if let Some(&(_, end, _)) = self.ranges.first() {
+            if end == self.pos && self.open.is_some() {
+                self.result.push_str("[/bgcolor]");
+                self.open = None;
+                self.ranges = &self.ranges[1..];
+            }
+        }
+    }
+
+    fn push_tag(&mut self, tag: &str) {
+        match self.open {
+            Some(color) => self.result.push_str(&format!("[/bgcolor]{}[bgcolor={}]", tag, color.to_html())),
+            None => self.result.push_str(tag),
+        }
+    }
+}
+
+/// Encodes text for use as a `[url]` meta value, which can't contain brackets.
+pub fn meta_encode(text: &str) -> String {
+    text.replace('%', "%25").replace('[', "%5B").replace(']', "%5D")
+}
+
+/// Reverses `meta_encode`.
+pub fn meta_decode(text: &str) -> String {
+    text.replace("%5B", "[").replace("%5D", "]").replace("%25", "%")
+}
+
 /// Finds the most similar string in a slice, within the given edit distance.
 /// Returns `None` if no match is close enough.
 pub fn fuzzy_match_string(
@@ -59,13 +183,9 @@ fn calculate_osa_distance(s1: &str, s2: &str) -> usize {
 
     // Iterative approach with 3 matrix rows.
     let mut row0 = vec![0usize; s2_len + 1]; // previous-previous
-    let mut row1 = vec![0usize; s2_len + 1]; // previous
+    let mut row1: Vec<usize> = (0..=s2_len).collect(); // previous
     let mut row2 = vec![0usize; s2_len + 1]; // current
 
-    for i in 0..=s2_len {
-        row1[i] = i;
-    }
-
     for i in 0..s1_len {
         row2[0] = i + 1;
 
@@ -107,3 +227,149 @@ fn is_valid_ascii_identifier(s: &str) -> bool {
         && !s.starts_with(|c: char| c.is_ascii_digit())
         && s.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
 }
+
+/// Parses a duration like `30s`, `10m`, `2h` or `1d` into seconds. A bare number means seconds.
+pub fn parse_duration(s: &str) -> Option<i64> {
+    let (digits, multiplier) = match s.chars().last()? {
+        's' => (&s[..s.len() - 1], 1),
+        'm' => (&s[..s.len() - 1], 60),
+        'h' => (&s[..s.len() - 1], 3600),
+        'd' => (&s[..s.len() - 1], 86400),
+        _ => (s, 1),
+    };
+    digits.parse::<i64>().ok().filter(|n| *n >= 0).and_then(|n| n.checked_mul(multiplier))
+}
+
+/// Removes `flag` where it is a whole word at the start or end of `args`, as often as it is.
+/// Returns the remaining text, trimmed but otherwise as typed, and whether the flag was found.
+pub fn take_flag<'a>(args: &'a str, flag: &str) -> (&'a str, bool) {
+    let mut rest = args.trim();
+    let mut found = false;
+    loop {
+        if let Some(r) = rest.strip_prefix(flag).filter(|r| r.is_empty() || r.starts_with(char::is_whitespace)) {
+            rest = r.trim_start();
+        } else if let Some(r) = rest.strip_suffix(flag).filter(|r| r.is_empty() || r.ends_with(char::is_whitespace)) {
+            rest = r.trim_end();
+        } else {
+            return (rest, found);
+        }
+        found = true;
+    }
+}
+
+/// Strips one pair of double quotes around `text`, if present.
+pub fn unquote(text: &str) -> &str {
+    text.strip_prefix('"').and_then(|t| t.strip_suffix('"')).unwrap_or(text)
+}
+
+/// Places paths without a scheme (`res://`, `user://`) under `user://`; absolute paths are kept.
+pub fn resolve_user_path(path: &str) -> String {
+    if path.contains("://") || path.starts_with('/') || path.get(1..3) == Some(":/") || path.get(1..3) == Some(":\\") {
+        path.to_string()
+    } else {
+        format!("user://{}", path)
+    }
+}
+
+/// Inserts `.suffix` before the file extension: `user://a.log` -> `user://a.suffix.log`.
+pub fn path_with_suffix(path: &str, suffix: &str) -> String {
+    let file_start = path.rfind('/').map_or(0, |i| i + 1);
+    match path[file_start..].rfind('.') {
+        Some(dot) if dot > 0 => {
+            let dot = file_start + dot;
+            format!("{}.{}{}", &path[..dot], suffix, &path[dot..])
+        }
+        _ => format!("{}.{}", path, suffix),
+    }
+}
+
+/// Returns the offset of the system's local time zone from UTC, in seconds.
+pub fn local_time_bias() -> i64 {
+    let bias_minutes = Time::singleton()
+        .get_time_zone_from_system()
+        .get("bias")
+        .and_then(|v| v.try_to::<i64>().ok())
+        .unwrap_or(0);
+    bias_minutes * 60
+}
+
+/// Formats a Unix timestamp as a local `YYYY-MM-DD HH:MM:SS` string.
+pub fn format_local_datetime(unix_time: i64) -> String {
+    Time::singleton()
+        .get_datetime_string_from_unix_time_ex(unix_time + local_time_bias())
+        .use_space(true)
+        .done()
+        .to_string()
+}
+
+/// Formats the time of day of a Unix timestamp as `HH:MM:SS.mmm`, shifted by `bias` seconds.
+pub fn format_clock_time(unix_time: f64, bias: i64) -> String {
+    let millis = ((unix_time + bias as f64) * 1000.0) as i64;
+    let millis_of_day = millis.rem_euclid(86_400_000);
+    format!(
+        "{:02}:{:02}:{:02}.{:03}",
+        millis_of_day / 3_600_000,
+        millis_of_day / 60_000 % 60,
+        millis_of_day / 1000 % 60,
+        millis_of_day % 1000
+    )
+}
+
+/// Formats a duration in seconds compactly, using its largest unit: `45s`, `12m`, `3h`, `5d`.
+pub fn format_relative_time(seconds: i64) -> String {
+    let seconds = seconds.max(0);
+    match seconds {
+        0..60 => format!("{}s", seconds),
+        60..3600 => format!("{}m", seconds / 60),
+        3600..86400 => format!("{}h", seconds / 3600),
+        _ => format!("{}d", seconds / 86400),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_duration_units() {
+        assert_eq!(parse_duration("30"), Some(30));
+        assert_eq!(parse_duration("30s"), Some(30));
+        assert_eq!(parse_duration("10m"), Some(600));
+        assert_eq!(parse_duration("2h"), Some(7200));
+        assert_eq!(parse_duration("1d"), Some(86400));
+        assert_eq!(parse_duration("0m"), Some(0));
+    }
+
+    #[test]
+    fn parse_duration_rejects_invalid() {
+        assert_eq!(parse_duration(""), None);
+        assert_eq!(parse_duration("m"), None);
+        assert_eq!(parse_duration("-5m"), None);
+        assert_eq!(parse_duration("5w"), None);
+        assert_eq!(parse_duration("1.5h"), None);
+    }
+
+    #[test]
+    fn parse_duration_overflow() {
+        assert_eq!(parse_duration("999999999999999999d"), None);
+        assert_eq!(parse_duration("99999999999999999999"), None);
+    }
+
+    #[test]
+    fn take_flag_keeps_the_rest_as_typed() {
+        assert_eq!(take_flag("my  file.txt", "--bbcode"), ("my  file.txt", false));
+        assert_eq!(take_flag("--bbcode my  file.txt", "--bbcode"), ("my  file.txt", true));
+        assert_eq!(take_flag("my  file.txt --bbcode", "--bbcode"), ("my  file.txt", true));
+        assert_eq!(take_flag("--bbcode", "--bbcode"), ("", true));
+        assert_eq!(take_flag("my--bbcode", "--bbcode"), ("my--bbcode", false));
+        assert_eq!(take_flag("\"a --bbcode\"", "--bbcode"), ("\"a --bbcode\"", false));
+    }
+
+    #[test]
+    fn unquote_strips_one_pair() {
+        assert_eq!(unquote("\"my file.txt\""), "my file.txt");
+        assert_eq!(unquote("my file.txt"), "my file.txt");
+        assert_eq!(unquote("\"open"), "\"open");
+        assert_eq!(unquote("\""), "\"");
+    }
+}
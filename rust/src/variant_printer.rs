@@ -0,0 +1,307 @@
+/// VariantPrinter: Renders Variants as indented, colored BBCode trees for the console output.
+/// Arrays, Dictionaries and Objects (with their exported properties) become foldable sections:
+/// each has a `[url]` toggle that TinyConsole routes back to `PrettyValue::toggle`, after which
+/// the value is rendered again in place. Sections deeper than `EXPAND_DEPTH` start folded.
+use godot::classes::{Node, Object, Resource, Script};
+use godot::global::{type_string, PropertyUsageFlags};
+use godot::prelude::*;
+use std::collections::HashSet;
+
+use crate::util;
+
+/// Sections up to this depth are expanded when first printed. Objects are only expanded at the root.
+const EXPAND_DEPTH: usize = 2;
+/// Entries shown per section; the rest are summarized.
+const MAX_ITEMS: usize = 100;
+const INDENT: &str = "    ";
+
+/// Colors for the parts of a printed value, and the image used for color swatches.
+#[derive(Clone)]
+pub struct ValueStyle {
+    pub string: Color,
+    pub number: Color,
+    /// `true`, `false` and `null`.
+    pub keyword: Color,
+    /// Type and class names.
+    pub type_name: Color,
+    /// Keys, brackets and fold toggles.
+    pub punctuation: Color,
+    /// Path of a white texture tinted to show Color values; swatches are left out if empty.
+    pub swatch_image: String,
+    /// Side of a swatch, in pixels.
+    pub swatch_size: i32,
+}
+
+impl Default for ValueStyle {
+    fn default() -> Self {
+        Self {
+            string: Color::from_rgba(0.6, 0.85, 0.45, 1.0),
+            number: Color::from_rgba(0.337, 0.62, 1.0, 1.0),
+            keyword: Color::from_rgba(1.0, 0.7, 0.3, 1.0),
+            type_name: Color::from_rgba(0.75, 0.55, 1.0, 1.0),
+            punctuation: Color::from_rgba(1.0, 1.0, 1.0, 0.35),
+            swatch_image: String::new(),
+            swatch_size: 14,
+        }
+    }
+}
+
+enum Section {
+    Array,
+    Dictionary,
+    Object(Gd<Object>),
+}
+
+/// A printed value and the sections the user folded or unfolded since.
+pub struct PrettyValue {
+    value: Variant,
+    /// Paths (child indices from the root) of sections whose fold state differs from the default.
+    toggled: HashSet<Vec<usize>>,
+}
+
+impl PrettyValue {
+    pub fn new(value: Variant) -> Self {
+        Self { value, toggled: HashSet::new() }
+    }
+
+    /// True if the value has sections, so its toggles need to be kept working.
+    pub fn is_foldable(&self) -> bool {
+        section_of(&self.value).is_some()
+    }
+
+    /// Folds or unfolds the section at `path`.
+    pub fn toggle(&mut self, path: Vec<usize>) {
+        if !self.toggled.remove(&path) {
+            self.toggled.insert(path);
+        }
+    }
+
+    /// Renders the value as BBCode lines joined by newlines. Toggle links are `<meta_prefix><path>`,
+    /// with the path's indices joined by dots (empty for the root).
+    pub fn render(&self, style: &ValueStyle, meta_prefix: &str) -> String {
+        let renderer = Renderer { value: self, style, meta_prefix };
+        let mut lines = Vec::new();
+        renderer.render(&self.value, &mut Vec::new(), "", String::new(), &mut lines);
+        lines.join("\n")
+    }
+}
+
+/// Parses a toggle path as written by `PrettyValue::render`.
+pub fn parse_path(text: &str) -> Option<Vec<usize>> {
+    if text.is_empty() {
+        return Some(Vec::new());
+    }
+    text.split('.').map(|i| i.parse().ok()).collect()
+}
+
+struct Renderer<'a> {
+    value: &'a PrettyValue,
+    style: &'a ValueStyle,
+    meta_prefix: &'a str,
+}
+
+impl Renderer<'_> {
+    fn render(&self, value: &Variant, path: &mut Vec<usize>, indent: &str, label: String, lines: &mut Vec<String>) {
+        let Some(section) = section_of(value) else {
+            lines.push(format!("{}{}{}", indent, label, self.scalar(value)));
+            return;
+        };
+        let (open, close) = match section {
+            Section::Array => ("[lb]", "[rb]"),
+            Section::Dictionary | Section::Object(_) => ("{", "}"),
+        };
+        let len = section_len(value, &section);
+        let header = self.header(value, &section, len);
+        if len == 0 {
+            lines.push(format!("{}{}{} {}", indent, label, header, self.punct(&format!("{}{}", open, close))));
+            return;
+        }
+
+        let default_expanded = match section {
+            Section::Object(_) => path.is_empty(),
+            _ => path.len() < EXPAND_DEPTH,
+        };
+        let expanded = default_expanded != self.value.toggled.contains(path);
+        let toggle = self.toggle_link(path, expanded);
+        if !expanded {
+            lines.push(format!("{}{}{} {} {}", indent, label, toggle, header, self.punct(&format!("{}…{}", open, close))));
+            return;
+        }
+
+        lines.push(format!("{}{}{} {} {}", indent, label, toggle, header, self.punct(open)));
+        let child_indent = format!("{}{}", indent, INDENT);
+        let entries = section_entries(value, &section);
+        for (i, (key, child)) in entries.iter().enumerate() {
+            path.push(i);
+            let key = match key {
+                Key::Index(i) => self.punct(&format!("{}: ", i)),
+                Key::Value(key) => format!("{}{}", self.scalar(key), self.punct(": ")),
+                Key::Property(name) => format!("{}{}", name, self.punct(": ")),
+            };
+            self.render(child, path, &child_indent, key, lines);
+            path.pop();
+        }
+        if len > entries.len() {
+            lines.push(format!("{}{}", child_indent, self.punct(&format!("… {} more", len - entries.len()))));
+        }
+        lines.push(format!("{}{}", indent, self.punct(close)));
+    }
+
+    fn header(&self, value: &Variant, section: &Section, len: usize) -> String {
+        match section {
+            Section::Object(object) => self.object_name(object),
+            _ => format!("{} {}", self.paint(&type_string(value.get_type().ord() as i64).to_string(), self.style.type_name), self.punct(&format!("({})", len))),
+        }
+    }
+
+    fn object_name(&self, object: &Gd<Object>) -> String {
+        let script_name = object
+            .get_script()
+            .and_then(|script| script.try_cast::<Script>().ok())
+            .map(|script| script.get_global_name().to_string())
+            .filter(|name| !name.is_empty());
+        let class = script_name.unwrap_or_else(|| object.get_class().to_string());
+        let mut name = format!("{}{}", self.paint(&class, self.style.type_name), self.punct(&format!("#{}", object.instance_id())));
+        if let Ok(node) = object.clone().try_cast::<Node>() {
+            name.push(' ');
+            name.push_str(&self.string(&node.get_name().to_string(), ""));
+        } else if let Ok(resource) = object.clone().try_cast::<Resource>() {
+            let path = resource.get_path();
+            if !path.is_empty() {
+                name.push(' ');
+                name.push_str(&self.string(&path.to_string(), ""));
+            }
+        }
+        name
+    }
+
+    fn scalar(&self, value: &Variant) -> String {
+        match value.get_type() {
+            VariantType::NIL => self.paint("null", self.style.keyword),
+            VariantType::BOOL => self.paint(&value.stringify().to_string(), self.style.keyword),
+            VariantType::INT | VariantType::FLOAT => self.paint(&value.stringify().to_string(), self.style.number),
+            VariantType::STRING => self.string(&value.stringify().to_string(), ""),
+            VariantType::STRING_NAME => self.string(&value.stringify().to_string(), "&"),
+            VariantType::NODE_PATH => self.string(&value.stringify().to_string(), "^"),
+            // An Object that failed to convert in `section_of`
+            VariantType::OBJECT => self.punct("<Freed Object>"),
+            VariantType::COLOR => {
+                let text = format!("{}{}", self.paint("Color", self.style.type_name), value.stringify());
+                match value.try_to::<Color>() {
+                    Ok(color) if !self.style.swatch_image.is_empty() => format!("{} {}", self.swatch(color), text),
+                    _ => text,
+                }
+            }
+            ty => {
+                let text = util::bbcode_escape(&value.stringify().to_string());
+                if text.starts_with('(') {
+                    // Math types print as a bare tuple; name them like their constructor
+                    format!("{}{}", self.paint(&type_string(ty.ord() as i64).to_string(), self.style.type_name), text)
+                } else {
+                    text
+                }
+            }
+        }
+    }
+
+    fn swatch(&self, color: Color) -> String {
+        let size = self.style.swatch_size;
+        format!("[img width={} height={} color={}]{}[/img]", size, size, color.to_html(), self.style.swatch_image)
+    }
+
+    fn string(&self, text: &str, sigil: &str) -> String {
+        let quoted = format!("{}\"{}\"", sigil, text.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n").replace('\t', "\\t"));
+        self.paint(&util::bbcode_escape(&quoted), self.style.string)
+    }
+
+    fn toggle_link(&self, path: &[usize], expanded: bool) -> String {
+        let path: Vec<String> = path.iter().map(usize::to_string).collect();
+        format!("[url={}{}]{}[/url]", self.meta_prefix, path.join("."), self.punct(if expanded { "▼" } else { "▶" }))
+    }
+
+    fn punct(&self, text: &str) -> String {
+        self.paint(text, self.style.punctuation)
+    }
+
+    fn paint(&self, text: &str, color: Color) -> String {
+        format!("[color={}]{}[/color]", color.to_html(), text)
+    }
+}
+
+enum Key {
+    Index(usize),
+    Value(Variant),
+    Property(String),
+}
+
+fn section_of(value: &Variant) -> Option<Section> {
+    match value.get_type() {
+        VariantType::ARRAY
+        | VariantType::PACKED_BYTE_ARRAY
+        | VariantType::PACKED_INT32_ARRAY
+        | VariantType::PACKED_INT64_ARRAY
+        | VariantType::PACKED_FLOAT32_ARRAY
+        | VariantType::PACKED_FLOAT64_ARRAY
+        | VariantType::PACKED_STRING_ARRAY
+        | VariantType::PACKED_VECTOR2_ARRAY
+        | VariantType::PACKED_VECTOR3_ARRAY
+        | VariantType::PACKED_COLOR_ARRAY
+        | VariantType::PACKED_VECTOR4_ARRAY => Some(Section::Array),
+        VariantType::DICTIONARY => Some(Section::Dictionary),
+        VariantType::OBJECT => value.try_to::<Gd<Object>>().ok().map(Section::Object),
+        _ => None,
+    }
+}
+
+fn section_len(value: &Variant, section: &Section) -> usize {
+    match section {
+        Section::Array | Section::Dictionary => value.call("size", &[]).try_to::<i64>().unwrap_or(0).max(0) as usize,
+        Section::Object(object) => exported_properties(object).len(),
+    }
+}
+
+/// Returns up to `MAX_ITEMS` entries. Arrays and Dictionaries are read through their
+/// scripting methods, so typed and packed arrays work the same as untyped ones.
+fn section_entries(value: &Variant, section: &Section) -> Vec<(Key, Variant)> {
+    match section {
+        Section::Array => {
+            let len = section_len(value, section).min(MAX_ITEMS);
+            (0..len).map(|i| (Key::Index(i), value.call("get", &[(i as i64).to_variant()]))).collect()
+        }
+        Section::Dictionary => {
+            let keys = value.call("keys", &[]);
+            let len = section_len(value, section).min(MAX_ITEMS);
+            (0..len)
+                .map(|i| {
+                    let key = keys.call("get", &[(i as i64).to_variant()]);
+                    let child = value.call("get", std::slice::from_ref(&key));
+                    (Key::Value(key), child)
+                })
+                .collect()
+        }
+        Section::Object(object) => exported_properties(object)
+            .into_iter()
+            .take(MAX_ITEMS)
+            .map(|name| {
+                let child = object.get(name.as_str());
+                (Key::Property(name), child)
+            })
+            .collect(),
+    }
+}
+
+/// Names of the properties shown in the inspector, in declaration order.
+fn exported_properties(object: &Gd<Object>) -> Vec<String> {
+    let hidden = PropertyUsageFlags::CATEGORY.ord() | PropertyUsageFlags::GROUP.ord() | PropertyUsageFlags::SUBGROUP.ord();
+    object
+        .get_property_list()
+        .iter_shared()
+        .filter_map(|property| {
+            let usage = property.get("usage")?.try_to::<i64>().ok()? as u64;
+            if usage & PropertyUsageFlags::EDITOR.ord() == 0 || usage & hidden != 0 {
+                return None;
+            }
+            property.get("name").map(|name| name.to_string())
+        })
+        .collect()
+}